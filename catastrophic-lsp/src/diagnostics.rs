@@ -0,0 +1,55 @@
+use catastrophic_core::{error::writer::ErrorWriter, span::Span};
+use lsp_types::{Diagnostic, DiagnosticRelatedInformation, DiagnosticSeverity, Location, Url};
+
+use crate::document::Lines;
+
+/// Collects the errors reported through an `ErrorProvider` as LSP diagnostics
+pub struct DiagnosticWriter<'a> {
+    uri: &'a Url,
+    lines: &'a Lines,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl<'a> DiagnosticWriter<'a> {
+    pub fn new(uri: &'a Url, lines: &'a Lines) -> Self {
+        Self {
+            uri,
+            lines,
+            diagnostics: Vec::new(),
+        }
+    }
+
+    pub fn finish(self) -> Vec<Diagnostic> {
+        self.diagnostics
+    }
+}
+
+impl ErrorWriter for DiagnosticWriter<'_> {
+    fn error(&mut self, span: Option<Span<()>>, message: &str) -> std::fmt::Result {
+        self.diagnostics.push(Diagnostic {
+            range: span
+                .map(|span| self.lines.range(span))
+                .unwrap_or_default(),
+            severity: Some(DiagnosticSeverity::ERROR),
+            source: Some("catastrophic".into()),
+            message: message.into(),
+            ..Diagnostic::default()
+        });
+
+        Ok(())
+    }
+
+    fn note(&mut self, span: Span<()>, message: &str) -> std::fmt::Result {
+        if let Some(diagnostic) = self.diagnostics.last_mut() {
+            diagnostic
+                .related_information
+                .get_or_insert_with(Vec::new)
+                .push(DiagnosticRelatedInformation {
+                    location: Location::new(self.uri.clone(), self.lines.range(span)),
+                    message: message.into(),
+                });
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,310 @@
+use std::{collections::BTreeSet, iter};
+
+use catastrophic_analyser::{
+    analyser::{Analyser, AnalysisOutput, CompileErrors},
+    index::{Definition, DefinitionKind},
+};
+use catastrophic_ast::ast::Builtin;
+use catastrophic_core::{
+    error::context::ErrorProvider,
+    span::{Location, Span},
+};
+use catastrophic_hir::hir;
+use catastrophic_parser::parser::Parser;
+use lsp_types::{self as lsp, CompletionItem, CompletionItemKind, Diagnostic, Hover, HoverContents, MarkupContent, MarkupKind, Position, Range, Url};
+
+use crate::diagnostics::DiagnosticWriter;
+
+mod test;
+
+const BUILTINS: [(Builtin, &str); 9] = [
+    (Builtin::Plus, "Add two numbers"),
+    (Builtin::Minus, "Subtract one number from another"),
+    (Builtin::Multiply, "Multiply two numbers"),
+    (Builtin::Divide, "Divide one number by another"),
+    (Builtin::Equals, "Compare two numbers for equality"),
+    (Builtin::GreaterThan, "Check whether one number is greater than another"),
+    (Builtin::LessThan, "Check whether one number is less than another"),
+    (Builtin::IfThenElse, "Choose between two values depending on a condition"),
+    (Builtin::Random, "Generate a random number within a range"),
+];
+
+/// The lines of a document, used to convert between source locations and LSP positions
+pub struct Lines {
+    lines: Vec<String>,
+}
+
+/// An open text document, analysed from the editor's (possibly unsaved) buffer contents
+pub struct Document {
+    uri: Url,
+    lines: Lines,
+    analysis: Option<AnalysisOutput>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+fn contains(span: Span<()>, location: Location) -> bool {
+    span.start <= location && location < span.end
+}
+
+impl Lines {
+    #[must_use]
+    pub fn new(text: &str) -> Self {
+        Self {
+            lines: text
+                .split('\n')
+                .map(String::from)
+                .collect(),
+        }
+    }
+
+    // Source locations count characters, whereas LSP positions count UTF-16 code units
+    fn chars(&self, line: usize) -> impl Iterator<Item = char> + '_ {
+        self.lines
+            .get(line)
+            .map(String::as_str)
+            .unwrap_or_default()
+            .chars()
+            .chain(iter::repeat(' '))
+    }
+
+    #[must_use]
+    pub fn position(&self, location: Location) -> Position {
+        let character: usize = self
+            .chars(location.line)
+            .take(location.col)
+            .map(char::len_utf16)
+            .sum();
+
+        Position::new(location.line as u32, character as u32)
+    }
+
+    #[must_use]
+    pub fn location(&self, position: Position) -> Location {
+        let mut remaining = position.character as usize;
+        let col = self
+            .chars(position.line as usize)
+            .take_while(|c| {
+                if remaining == 0 {
+                    false
+                } else {
+                    remaining = remaining.saturating_sub(c.len_utf16());
+                    true
+                }
+            })
+            .count();
+
+        Location::new(position.line as usize, col)
+    }
+
+    #[must_use]
+    pub fn range(&self, span: Span<()>) -> Range {
+        Range::new(self.position(span.start), self.position(span.end))
+    }
+}
+
+impl Document {
+    #[must_use]
+    pub fn new(uri: Url, text: &str) -> Self {
+        let lines = Lines::new(text);
+        let mut writer = DiagnosticWriter::new(&uri, &lines);
+        let analysis = Self::analyse(text, &mut writer).unwrap_or_default();
+        let diagnostics = writer.finish();
+
+        Self {
+            uri,
+            lines,
+            analysis,
+            diagnostics,
+        }
+    }
+
+    fn analyse(text: &str, writer: &mut DiagnosticWriter) -> Result<Option<AnalysisOutput>, std::fmt::Error> {
+        let output = match Parser::with_str(text)
+            .permissive(true)
+            .parse()
+        {
+            Ok(output) => output,
+            Err(error) => {
+                error.write_errors(writer)?;
+                return Ok(None);
+            }
+        };
+
+        for error in &output.errors {
+            error.write_errors(writer)?;
+        }
+
+        let analysis = Analyser::analyse_ast_permissive(output.ast);
+        CompileErrors::from(analysis.errors.clone()).write_errors(writer)?;
+
+        Ok(Some(analysis))
+    }
+
+    #[must_use]
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        &self.diagnostics
+    }
+
+    fn location(&self, span: Span<()>) -> lsp::Location {
+        lsp::Location::new(self.uri.clone(), self.lines.range(span))
+    }
+
+    // Editors commonly place the cursor just after the symbol it refers to, so fall back to the previous column
+    fn lookup(&self, position: Position) -> Option<(&AnalysisOutput, &Definition)> {
+        let analysis = self.analysis.as_ref()?;
+        let location = self.lines.location(position);
+
+        analysis
+            .references
+            .definition_at(location)
+            .or_else(|| {
+                let col = location.col.checked_sub(1)?;
+                analysis
+                    .references
+                    .definition_at(Location::new(location.line, col))
+            })
+            .map(|definition| (analysis, definition))
+    }
+
+    #[must_use]
+    pub fn definition(&self, position: Position) -> Option<lsp::Location> {
+        self.lookup(position)
+            .map(|(_, definition)| self.location(definition.span()))
+    }
+
+    #[must_use]
+    pub fn references(&self, position: Position, include_declaration: bool) -> Vec<lsp::Location> {
+        let Some((_, definition)) = self.lookup(position) else {
+            return Vec::new();
+        };
+
+        include_declaration
+            .then(|| definition.span())
+            .into_iter()
+            .chain(
+                definition
+                    .uses
+                    .iter()
+                    .map(|reference| reference.span),
+            )
+            .map(|span| self.location(span))
+            .collect()
+    }
+
+    #[must_use]
+    pub fn hover(&self, position: Position) -> Option<Hover> {
+        let (analysis, definition) = self.lookup(position)?;
+
+        let signature = format!("```\n{}\n```", signature(analysis, definition));
+        let value = match (
+            definition.kind,
+            &analysis.ir[definition.block]
+                .source
+                .label,
+        ) {
+            (DefinitionKind::Label, _) => signature,
+            (DefinitionKind::Arg, Some(label)) => format!("{signature}\n\nArgument of `{label}`"),
+            (DefinitionKind::Arg, None) => format!("{signature}\n\nArgument of an anonymous block"),
+        };
+
+        Some(Hover {
+            contents: HoverContents::Markup(MarkupContent {
+                kind: MarkupKind::Markdown,
+                value,
+            }),
+            range: None,
+        })
+    }
+
+    #[must_use]
+    pub fn completions(&self, position: Position) -> Vec<CompletionItem> {
+        let mut items = Vec::new();
+
+        if let Some(analysis) = &self.analysis {
+            let location = self.lines.location(position);
+            let mut seen = BTreeSet::new();
+            let mut block = Some(enclosing_block(analysis, location));
+
+            while let Some(index) = block {
+                // Labels are bound after args, so they shadow any arg of the same name in the same block
+                let mut definitions = definitions_in(analysis, index).collect::<Vec<_>>();
+                definitions.sort_by_key(|definition| definition.kind);
+
+                for definition in definitions {
+                    if seen.insert(definition.name.data.as_str()) {
+                        items.push(CompletionItem {
+                            label: definition.name.data.clone(),
+                            kind: Some(match definition.kind {
+                                DefinitionKind::Label => CompletionItemKind::FUNCTION,
+                                DefinitionKind::Arg => CompletionItemKind::VARIABLE,
+                            }),
+                            detail: Some(signature(analysis, definition)),
+                            ..CompletionItem::default()
+                        });
+                    }
+                }
+
+                block = analysis.ir[index].source.parent;
+            }
+        }
+
+        items.extend(
+            BUILTINS
+                .iter()
+                .map(|(builtin, description)| CompletionItem {
+                    label: builtin.to_string(),
+                    kind: Some(CompletionItemKind::OPERATOR),
+                    detail: Some((*description).into()),
+                    ..CompletionItem::default()
+                }),
+        );
+
+        items
+    }
+}
+
+fn definitions_in(analysis: &AnalysisOutput, block: usize) -> impl Iterator<Item = &Definition> {
+    analysis
+        .references
+        .definitions()
+        .filter(move |definition| definition.block == block)
+}
+
+// The innermost block whose source covers the location, falling back to the top level
+fn enclosing_block(analysis: &AnalysisOutput, location: Location) -> usize {
+    analysis
+        .ir
+        .iter()
+        .enumerate()
+        .filter_map(|(index, block)| {
+            block
+                .source
+                .span
+                .filter(|span| contains(*span, location))
+                .map(|span| (span.start, index))
+        })
+        .max()
+        .map_or(0, |(_, index)| index)
+}
+
+fn signature(analysis: &AnalysisOutput, definition: &Definition) -> String {
+    let name = &definition.name.data;
+
+    if definition.kind == DefinitionKind::Arg {
+        return name.clone();
+    }
+
+    match analysis.ir[definition.block].lookup_symbol(name) {
+        Some(hir::Value::Function(hir::Function::Block(index))) => {
+            let args = definitions_in(analysis, index)
+                .filter(|arg| arg.kind == DefinitionKind::Arg)
+                .flat_map(|arg| [arg.name.data.as_str(), " -> "])
+                .collect::<String>();
+
+            format!("{name}: {args}{{ ... }}")
+        }
+        Some(hir::Value::Function(hir::Function::Builtin(builtin))) => format!("{name}: {builtin}"),
+        Some(hir::Value::Number(value)) => format!("{name}: {value}"),
+        Some(hir::Value::Arg(_)) | None => name.clone(),
+    }
+}
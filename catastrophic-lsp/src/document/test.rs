@@ -0,0 +1,136 @@
+#![cfg(test)]
+
+use lsp_types::{DiagnosticSeverity, HoverContents};
+
+use super::*;
+
+fn document(input: &str) -> Document {
+    Document::new(Url::parse("file:///test.cat").unwrap(), input)
+}
+
+fn range(from_line: u32, from_col: u32, to_line: u32, to_col: u32) -> Range {
+    Range::new(Position::new(from_line, from_col), Position::new(to_line, to_col))
+}
+
+fn labels(items: &[CompletionItem]) -> Vec<&str> {
+    items
+        .iter()
+        .filter(|item| item.kind != Some(CompletionItemKind::OPERATOR))
+        .map(|item| item.label.as_str())
+        .collect()
+}
+
+fn hover_text(document: &Document, position: Position) -> String {
+    match document
+        .hover(position)
+        .unwrap()
+        .contents
+    {
+        HoverContents::Markup(content) => content.value,
+        contents => panic!("unexpected hover contents {contents:?}"),
+    }
+}
+
+#[test]
+fn diagnostics_for_undefined_symbols() {
+    let document = document("a: 5\na b +");
+    let diagnostics = document.diagnostics();
+
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(diagnostics[0].range, range(1, 2, 1, 3));
+    assert_eq!(diagnostics[0].severity, Some(DiagnosticSeverity::ERROR));
+    assert_eq!(diagnostics[0].message, "Use of undefined symbol `b`");
+}
+
+#[test]
+fn diagnostics_for_parse_errors() {
+    let document = document("a: 5 }");
+
+    assert!(!document.diagnostics().is_empty());
+}
+
+#[test]
+fn no_diagnostics_for_valid_input() {
+    assert!(document("a: x -> { x x + () }\n1 a ()")
+        .diagnostics()
+        .is_empty());
+}
+
+#[test]
+fn definition_of_label() {
+    let document = document("a: 5\na a +");
+    let location = document
+        .definition(Position::new(1, 2))
+        .unwrap();
+
+    assert_eq!(location.range, range(0, 0, 0, 1));
+}
+
+#[test]
+fn definition_from_end_of_symbol() {
+    let document = document("abc: 5\nabc");
+    let location = document
+        .definition(Position::new(1, 3))
+        .unwrap();
+
+    assert_eq!(location.range, range(0, 0, 0, 3));
+}
+
+#[test]
+fn references_of_arg() {
+    let document = document("x -> { x x }");
+    let ranges = |include_declaration| {
+        document
+            .references(Position::new(0, 7), include_declaration)
+            .into_iter()
+            .map(|location| location.range)
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(ranges(false), [range(0, 7, 0, 8), range(0, 9, 0, 10)]);
+    assert_eq!(ranges(true), [range(0, 0, 0, 1), range(0, 7, 0, 8), range(0, 9, 0, 10)]);
+}
+
+#[test]
+fn positions_count_utf16_units() {
+    let document = document("😀: 5\n😀");
+    let location = document
+        .definition(Position::new(1, 0))
+        .unwrap();
+
+    assert_eq!(location.range, range(0, 0, 0, 2));
+}
+
+#[test]
+fn completion_of_symbols_in_scope() {
+    let document = document("a: x -> {\n    b: 5\n    \n}\nc: y -> {}");
+    let items = document.completions(Position::new(2, 4));
+
+    assert_eq!(labels(&items), ["b", "x", "a", "c"]);
+    assert!(items
+        .iter()
+        .any(|item| item.label == "?"));
+}
+
+#[test]
+fn completion_at_top_level() {
+    let document = document("a: x -> {\n    b: 5\n}\n");
+    let items = document.completions(Position::new(3, 0));
+
+    assert_eq!(labels(&items), ["a"]);
+}
+
+#[test]
+fn hover_shows_args() {
+    let document = document("cons: list -> value -> { value }\n1 2 cons ()");
+
+    assert_eq!(hover_text(&document, Position::new(1, 5)), "```\ncons: list -> value -> { ... }\n```");
+    assert_eq!(hover_text(&document, Position::new(0, 26)), "```\nvalue\n```\n\nArgument of `cons`");
+}
+
+#[test]
+fn hover_over_nothing() {
+    assert!(document("a: 5\n1 2 +")
+        .hover(Position::new(1, 0))
+        .is_none());
+}
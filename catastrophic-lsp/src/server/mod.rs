@@ -0,0 +1,171 @@
+use std::collections::HashMap;
+
+use anyhow::Result;
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, Response};
+use lsp_types::{
+    notification::{DidChangeTextDocument, DidCloseTextDocument, DidOpenTextDocument, Notification as LspNotification, PublishDiagnostics},
+    request::{Completion, GotoDefinition, HoverRequest, References, Request as LspRequest},
+    CompletionResponse, Diagnostic, GotoDefinitionResponse, PublishDiagnosticsParams, Url,
+};
+
+use crate::document::Document;
+
+mod test;
+
+pub struct Server {
+    connection: Connection,
+    documents: HashMap<Url, Document>,
+}
+
+impl Server {
+    pub fn new(connection: Connection) -> Self {
+        Self {
+            connection,
+            documents: HashMap::new(),
+        }
+    }
+
+    pub fn run(mut self) -> Result<()> {
+        let receiver = self.connection.receiver.clone();
+
+        for message in &receiver {
+            match message {
+                Message::Request(request) => {
+                    if self
+                        .connection
+                        .handle_shutdown(&request)?
+                    {
+                        return Ok(());
+                    }
+
+                    self.handle_request(request)?;
+                }
+                Message::Notification(notification) => self.handle_notification(notification)?,
+                Message::Response(_) => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn handle_request(&self, request: Request) -> Result<()> {
+        match request.method.as_str() {
+            GotoDefinition::METHOD => self.respond::<GotoDefinition, _>(request, |server, params| {
+                let position = params.text_document_position_params;
+                server
+                    .document(&position.text_document.uri)?
+                    .definition(position.position)
+                    .map(GotoDefinitionResponse::Scalar)
+            }),
+            References::METHOD => self.respond::<References, _>(request, |server, params| {
+                let position = params.text_document_position;
+                Some(
+                    server
+                        .document(&position.text_document.uri)?
+                        .references(position.position, params.context.include_declaration),
+                )
+            }),
+            HoverRequest::METHOD => self.respond::<HoverRequest, _>(request, |server, params| {
+                let position = params.text_document_position_params;
+                server
+                    .document(&position.text_document.uri)?
+                    .hover(position.position)
+            }),
+            Completion::METHOD => self.respond::<Completion, _>(request, |server, params| {
+                let position = params.text_document_position;
+                Some(CompletionResponse::Array(
+                    server
+                        .document(&position.text_document.uri)?
+                        .completions(position.position),
+                ))
+            }),
+            _ => self.send(Response::new_err(
+                request.id,
+                ErrorCode::MethodNotFound as i32,
+                format!("Unsupported request `{}`", request.method),
+            )),
+        }
+    }
+
+    fn handle_notification(&mut self, notification: Notification) -> Result<()> {
+        match notification.method.as_str() {
+            DidOpenTextDocument::METHOD => self.notified::<DidOpenTextDocument, _>(notification, |server, params| {
+                server.update(params.text_document.uri, &params.text_document.text)
+            }),
+            DidChangeTextDocument::METHOD => self.notified::<DidChangeTextDocument, _>(notification, |server, params| {
+                // Documents are synchronised in full, so only the final change matters
+                match params.content_changes.last() {
+                    Some(change) => server.update(params.text_document.uri, &change.text),
+                    None => Ok(()),
+                }
+            }),
+            DidCloseTextDocument::METHOD => self.notified::<DidCloseTextDocument, _>(notification, |server, params| {
+                server
+                    .documents
+                    .remove(&params.text_document.uri);
+                server.publish(params.text_document.uri, Vec::new())
+            }),
+            _ => Ok(()),
+        }
+    }
+
+    fn document(&self, uri: &Url) -> Option<&Document> {
+        self.documents.get(uri)
+    }
+
+    fn update(&mut self, uri: Url, text: &str) -> Result<()> {
+        let document = Document::new(uri.clone(), text);
+        self.publish(uri.clone(), document.diagnostics().to_vec())?;
+        self.documents.insert(uri, document);
+
+        Ok(())
+    }
+
+    fn publish(&self, uri: Url, diagnostics: Vec<Diagnostic>) -> Result<()> {
+        let params = PublishDiagnosticsParams::new(uri, diagnostics, None);
+        self.connection
+            .sender
+            .send(Message::Notification(Notification::new(PublishDiagnostics::METHOD.into(), params)))?;
+
+        Ok(())
+    }
+
+    fn respond<R, F>(&self, request: Request, handler: F) -> Result<()>
+    where
+        R: LspRequest,
+        F: FnOnce(&Self, R::Params) -> R::Result,
+    {
+        let id = request.id.clone();
+
+        match request.extract::<R::Params>(R::METHOD) {
+            Ok((id, params)) => {
+                let result = handler(self, params);
+                self.send(Response::new_ok(id, result))
+            }
+            Err(error) => self.send(Response::new_err(id, ErrorCode::InvalidParams as i32, error.to_string())),
+        }
+    }
+
+    // Notifications can't be answered, so one the server can't make sense of is only logged rather than ending the session
+    fn notified<N, F>(&mut self, notification: Notification, handler: F) -> Result<()>
+    where
+        N: LspNotification,
+        F: FnOnce(&mut Self, N::Params) -> Result<()>,
+    {
+        match notification.extract::<N::Params>(N::METHOD) {
+            Ok(params) => handler(self, params),
+            Err(error) => {
+                eprintln!("Ignoring malformed `{}` notification: {error}", N::METHOD);
+                Ok(())
+            }
+        }
+    }
+
+    fn send(&self, response: Response) -> Result<()> {
+        self.connection
+            .sender
+            .send(Message::Response(response))?;
+
+        Ok(())
+    }
+}
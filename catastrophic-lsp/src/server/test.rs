@@ -0,0 +1,75 @@
+#![cfg(test)]
+
+use std::thread::{self, JoinHandle};
+
+use lsp_server::{Connection, ErrorCode, Message, Notification, Request, RequestId, Response};
+use lsp_types::{
+    notification::{DidOpenTextDocument, Exit, Notification as LspNotification},
+    request::{HoverRequest, Request as LspRequest, Shutdown},
+};
+use serde_json::json;
+
+use super::Server;
+
+fn start() -> (Connection, JoinHandle<()>) {
+    let (server, client) = Connection::memory();
+    let handle = thread::spawn(move || Server::new(server).run().unwrap());
+
+    (client, handle)
+}
+
+fn request(client: &Connection, id: i32, method: &str, params: serde_json::Value) -> Response {
+    client
+        .sender
+        .send(Message::Request(Request::new(RequestId::from(id), method.to_owned(), params)))
+        .unwrap();
+
+    match client.receiver.recv().unwrap() {
+        Message::Response(response) => response,
+        message => panic!("expected a response, found {message:?}"),
+    }
+}
+
+fn stop(client: &Connection, handle: JoinHandle<()>) {
+    request(client, 0, Shutdown::METHOD, json!(null));
+    client
+        .sender
+        .send(Message::Notification(Notification::new(Exit::METHOD.to_owned(), json!(null))))
+        .unwrap();
+
+    handle.join().unwrap();
+}
+
+#[test]
+fn malformed_request_is_answered_with_an_error() {
+    let (client, handle) = start();
+
+    let response = request(&client, 1, HoverRequest::METHOD, json!({ "nonsense": true }));
+    assert_eq!(response.id, RequestId::from(1));
+    assert_eq!(response.error.map(|error| error.code), Some(ErrorCode::InvalidParams as i32));
+
+    stop(&client, handle);
+}
+
+#[test]
+fn malformed_notification_is_ignored() {
+    let (client, handle) = start();
+
+    client
+        .sender
+        .send(Message::Notification(Notification::new(
+            DidOpenTextDocument::METHOD.to_owned(),
+            json!({ "nonsense": true }),
+        )))
+        .unwrap();
+
+    // The server is still answering requests afterwards
+    let params = json!({
+        "textDocument": { "uri": "file:///test.cat" },
+        "position": { "line": 0, "character": 0 },
+    });
+    let response = request(&client, 1, HoverRequest::METHOD, params);
+    assert!(response.error.is_none());
+
+    stop(&client, handle);
+}
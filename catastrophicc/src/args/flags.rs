@@ -17,3 +17,15 @@ pub enum Optimization {
 pub enum List {
     Passes,
 }
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum DocFormat {
+    Markdown,
+    Html,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum RemarksFormat {
+    Text,
+    Json,
+}
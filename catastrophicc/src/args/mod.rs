@@ -1,15 +1,23 @@
 use std::path::PathBuf;
 
-use clap::Parser;
+use catastrophic_core::span::Location;
+use clap::{Parser, Subcommand};
 
 pub mod flags;
 
 #[derive(Debug, Clone, Parser)]
+#[command(args_conflicts_with_subcommands = true, subcommand_negates_reqs = true)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     // Debug options
     #[arg(long, help_heading = "Debug")]
     pub list: Option<flags::List>,
 
+    #[arg(long, value_name = "FORMAT", help_heading = "Debug")]
+    pub remarks: Option<flags::RemarksFormat>,
+
     // Compilation options
     #[arg(long)]
     pub debug: Option<flags::DebugMode>,
@@ -23,14 +31,67 @@ pub struct Args {
     #[arg(long, default_value = "all")]
     pub opt: flags::Optimization,
 
+    #[arg(long, value_name = "PASSES")]
+    pub passes: Option<String>,
+
+    #[arg(long, value_name = "PASS", value_delimiter = ',')]
+    pub skip_pass: Vec<String>,
+
+    #[arg(long, value_name = "INSTRS")]
+    pub inline_threshold: Option<usize>,
+
     #[arg(long)]
-    pub skip_pass: Option<String>,
+    pub evaluate: bool,
+
+    #[arg(long, value_name = "STEPS", requires = "evaluate")]
+    pub evaluation_budget: Option<usize>,
+
+    // Refactoring options
+    /// Rename the symbol at this position, with lines and columns counted from 0 as error messages show them
+    #[arg(long, value_name = "LINE:COL", value_parser = parse_location, requires = "to", help_heading = "Refactoring")]
+    pub rename: Option<Location>,
+
+    #[arg(long, value_name = "NAME", requires = "rename", help_heading = "Refactoring")]
+    pub to: Option<String>,
 
     // Compilation input
     #[arg(required_unless_present = "list")]
     pub input: Option<PathBuf>,
 }
 
+#[derive(Debug, Clone, Subcommand)]
+pub enum Command {
+    /// Generate documentation from the comment above each symbol, without compiling the program
+    Doc(DocArgs),
+}
+
+#[derive(Debug, Clone, clap::Args)]
+pub struct DocArgs {
+    #[arg(long, default_value = "markdown")]
+    pub format: flags::DocFormat,
+
+    /// Write the documentation to this file rather than to stdout
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    pub input: PathBuf,
+}
+
+fn parse_location(input: &str) -> Result<Location, String> {
+    let (line, col) = input
+        .split_once(':')
+        .ok_or_else(|| format!("expected `LINE:COL`, found `{input}`"))?;
+
+    let line = line
+        .parse()
+        .map_err(|_| format!("invalid line `{line}`"))?;
+    let col = col
+        .parse()
+        .map_err(|_| format!("invalid column `{col}`"))?;
+
+    Ok(Location::new(line, col))
+}
+
 impl Args {
     pub fn try_parse() -> Result<Self, clap::error::Error> {
         <Self as Parser>::try_parse()
@@ -1,20 +1,31 @@
-use std::{fmt::Debug, path::PathBuf};
+use std::{fmt::Debug, fs, path::PathBuf};
 
-use anyhow::Result;
+use anyhow::{bail, Result};
 use args::{
-    flags::{DebugMode, List, Optimization},
-    Args,
+    flags::{DebugMode, DocFormat, List, Optimization, RemarksFormat},
+    Args, Command, DocArgs,
+};
+use catastrophic_analyser::{
+    rename::apply_edits,
+    stage::{AnalysisStage, RenameStage},
 };
-use catastrophic_analyser::stage::AnalysisStage;
 use catastrophic_compiler::stage::CompilationStage;
 use catastrophic_core::{
     error::context::ErrorContext,
     pretty::{PrettyDebug, PrettyDebugger},
     profiling::TimeKeeper,
+    span::Location,
     stage::{pipeline, Continue, Extend, Pipeline, PipelineResult, Stage, StageContext},
 };
-use catastrophic_hir_optimizer::{optimizer::Options, stage::OptimizationStage};
-use catastrophic_parser::stage::ParseStage;
+use catastrophic_doc::{
+    render::{Html, Markdown},
+    stage::DocStage,
+};
+use catastrophic_hir_optimizer::{
+    optimizer::{remark::Remark, Options},
+    stage::{OptimizationStage, RemarksStage},
+};
+use catastrophic_parser::{lexer::is_identifier, stage::ParseStage};
 
 mod args;
 
@@ -28,34 +39,129 @@ impl App {
     }
 
     fn run(&self) -> Result<()> {
-        if let Some(list) = self.args.list {
+        if let Some(Command::Doc(doc)) = &self.args.command {
+            Self::doc(doc)
+        } else if let Some(list) = self.args.list {
             match list {
                 List::Passes => {
-                    for pass in OptimizationStage::pass_names() {
+                    for pass in OptimizationStage::pass_ids() {
                         println!("{pass}");
                     }
                 }
             }
 
             Ok(())
+        } else if let (Some(location), Some(name)) = (self.args.rename, &self.args.to) {
+            self.rename(location, name)
+        } else if let Some(format) = self.args.remarks {
+            self.remarks(format)
         } else {
             let pipeline_context = self.make_context()?;
-            let pipeline = self.make_pipeline();
+            let pipeline = self.make_pipeline()?;
 
             let result = pipeline.run(pipeline_context);
             self.finish(result)
         }
     }
 
+    fn rename(&self, location: Location, name: &str) -> Result<()> {
+        if !is_identifier(name) {
+            bail!("`{name}` is not a valid symbol name");
+        }
+
+        let pipeline_context = self.make_context()?;
+
+        let result = pipeline(ParseStage.stage(), |_| ())
+            .and_then(RenameStage::new(location, name.to_owned()).stage(), |_| ())
+            .run(pipeline_context);
+
+        match result {
+            PipelineResult::Ok(context) => {
+                let path = self.args.input.as_ref().unwrap();
+                let source = fs::read_to_string(path)?;
+                fs::write(path, apply_edits(&source, &context.input))?;
+                Ok(())
+            }
+            PipelineResult::Cancelled => Ok(()),
+            PipelineResult::Err(error) => Err(error),
+        }
+    }
+
+    // Only the parser is needed to find the symbols and their comments, so programs which wouldn't compile can still be documented
+    fn doc(args: &DocArgs) -> Result<()> {
+        let pipeline_context = Self::context_for(args.input.clone())?;
+
+        let result = pipeline(ParseStage.stage(), |_| ())
+            .and_then(DocStage.stage(), |_| ())
+            .run(pipeline_context);
+
+        match result {
+            PipelineResult::Ok(context) => {
+                let source = args.input.display().to_string();
+
+                let rendered = match args.format {
+                    DocFormat::Markdown => Markdown::new(&source, &context.input).to_string(),
+                    DocFormat::Html => Html::new(&source, &context.input).to_string(),
+                };
+
+                match &args.output {
+                    Some(path) => fs::write(path, rendered)?,
+                    None => print!("{rendered}"),
+                }
+
+                Ok(())
+            }
+            PipelineResult::Cancelled => Ok(()),
+            PipelineResult::Err(error) => Err(error),
+        }
+    }
+
+    fn remarks(&self, format: RemarksFormat) -> Result<()> {
+        let pipeline_context = self.make_context()?;
+
+        let result = pipeline(ParseStage.stage(), |_| ())
+            .and_then(AnalysisStage.stage(), |_| ())
+            .and_then(RemarksStage::new(self.optimization_options()?).stage(), |_| ())
+            .run(pipeline_context);
+
+        match result {
+            PipelineResult::Ok(context) => {
+                match format {
+                    RemarksFormat::Text => {
+                        for remark in &context.input {
+                            println!("{remark}");
+                        }
+                    }
+                    RemarksFormat::Json => {
+                        let remarks = context
+                            .input
+                            .iter()
+                            .map(Remark::to_json)
+                            .collect();
+
+                        println!("{}", serde_json::to_string_pretty(&serde_json::Value::Array(remarks))?);
+                    }
+                }
+
+                Ok(())
+            }
+            PipelineResult::Cancelled => Ok(()),
+            PipelineResult::Err(error) => Err(error),
+        }
+    }
+
     fn make_context(&self) -> Result<StageContext<PathBuf>> {
-        let path = self.args.input.clone().unwrap();
+        Self::context_for(self.args.input.clone().unwrap())
+    }
+
+    fn context_for(path: PathBuf) -> Result<StageContext<PathBuf>> {
         let error_context = ErrorContext::from_file(&path)?;
         let time_keeper = TimeKeeper::new(&"Overall");
         let pipeline_context = StageContext::new(path, time_keeper, error_context);
         Ok(pipeline_context)
     }
 
-    fn make_pipeline(&self) -> impl Pipeline<anyhow::Error, Start = StageContext<PathBuf>, End = StageContext<()>> {
+    fn make_pipeline(&self) -> Result<impl Pipeline<anyhow::Error, Start = StageContext<PathBuf>, End = StageContext<()>>> {
         let source_filename = PathBuf::from(
             self.args
                 .input
@@ -65,20 +171,40 @@ impl App {
                 .unwrap(),
         );
 
-        pipeline(ParseStage.stage(), self.debug_callback(DebugMode::Ast))
+        Ok(pipeline(ParseStage.stage(), self.debug_callback(DebugMode::Ast))
             .and_then(AnalysisStage.stage(), self.debug_callback(DebugMode::Hir))
             .and_then(
-                OptimizationStage::new(if let Optimization::None = self.args.opt {
-                    Options::no_passes()
-                } else if let Some(ref pass) = self.args.skip_pass {
-                    Options::without_pass(pass)
-                } else {
-                    Options::all_passes()
-                })
-                .stage(),
+                OptimizationStage::new(self.optimization_options()?).stage(),
                 self.debug_callback(DebugMode::Mir),
             )
-            .and_then(CompilationStage::new(source_filename).stage(), |_| ())
+            .and_then(CompilationStage::new(source_filename).stage(), |_| ()))
+    }
+
+    fn optimization_options(&self) -> Result<Options> {
+        let mut options = if let Optimization::None = self.args.opt {
+            Options::no_passes()
+        } else if let Some(ref spec) = self.args.passes {
+            Options::from_spec(spec)?
+        } else {
+            Options::all_passes()
+        };
+
+        for pass in &self.args.skip_pass {
+            options = options.without_pass(pass)?;
+        }
+
+        if self.args.evaluate {
+            options = options.with_pass("partial-evaluation")?;
+        }
+
+        if let Some(budget) = self.args.evaluation_budget {
+            options = options.with_evaluation_budget(budget);
+        }
+
+        Ok(match self.args.inline_threshold {
+            Some(threshold) => options.with_inline_threshold(threshold),
+            None => options,
+        })
     }
 
     fn debug_callback<Input: Debug + PrettyDebug>(&self, debug: DebugMode) -> for<'a> fn(&'a StageContext<Input>) -> Continue {
@@ -7,9 +7,25 @@ use catastrophic_core::{
     profiling::TimeKeeper,
     stage::{pipeline, Extend, Pipeline, PipelineResult, Stage, StageContext},
 };
-use catastrophic_interpreter::stage::InterpreterStage;
+use catastrophic_hir_optimizer::{optimizer::Options, stage::OptimizationStage};
+use catastrophic_interpreter::{
+    interpreter::{Arithmetic, Options as InterpreterOptions},
+    stage::{InterpreterStage, MirInterpreterStage},
+};
 use catastrophic_parser::stage::ParseStage;
-use clap::Parser as ArgParser;
+use clap::{Parser as ArgParser, ValueEnum};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum Optimization {
+    None,
+    All,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+enum Overflow {
+    Trap,
+    Wrap,
+}
 
 #[derive(Debug, Clone, ArgParser)]
 struct Args {
@@ -17,6 +33,18 @@ struct Args {
 
     #[arg(short, long)]
     profile: bool,
+
+    /// Optimize the program before running it, which runs it from MIR rather than HIR
+    #[arg(long, default_value = "none")]
+    opt: Optimization,
+
+    /// Report an error rather than letting blocks call each other more than this many calls deep
+    #[arg(long)]
+    max_depth: Option<usize>,
+
+    /// Whether arithmetic which overflows reports an error or wraps around
+    #[arg(long, default_value = "trap")]
+    overflow: Overflow,
 }
 
 fn main() -> Result<()> {
@@ -26,10 +54,26 @@ fn main() -> Result<()> {
     let time_keeper = TimeKeeper::new(&"Overall");
     let pipeline_context = StageContext::new(args.input, time_keeper, error_context);
 
-    let result = pipeline(ParseStage.stage(), |_| ())
-        .and_then(AnalysisStage.stage(), |_| ())
-        .and_then(InterpreterStage.stage(), |_| ())
-        .run(pipeline_context);
+    let arithmetic = match args.overflow {
+        Overflow::Trap => Arithmetic::Trapping,
+        Overflow::Wrap => Arithmetic::Wrapping,
+    };
+
+    let interpreter_options = InterpreterOptions::default()
+        .with_max_depth(args.max_depth)
+        .with_arithmetic(arithmetic);
+
+    let result = match args.opt {
+        Optimization::None => pipeline(ParseStage.stage(), |_| ())
+            .and_then(AnalysisStage.stage(), |_| ())
+            .and_then(InterpreterStage::new(interpreter_options).stage(), |_| ())
+            .run(pipeline_context),
+        Optimization::All => pipeline(ParseStage.stage(), |_| ())
+            .and_then(AnalysisStage.stage(), |_| ())
+            .and_then(OptimizationStage::new(Options::all_passes()).stage(), |_| ())
+            .and_then(MirInterpreterStage::new(interpreter_options).stage(), |_| ())
+            .run(pipeline_context),
+    };
 
     match result {
         PipelineResult::Ok(context) => {
@@ -0,0 +1,12 @@
+#![no_main]
+
+use catastrophic_analyser::analyser::Analyser;
+use catastrophic_parser::parser::Parser;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    // Only input which parses without errors is analysed, as the analyser expects a well formed tree
+    if let Ok(output) = Parser::with_str(input).parse() {
+        let _ = Analyser::analyse_ast(output.ast);
+    }
+});
@@ -0,0 +1,8 @@
+#![no_main]
+
+use catastrophic_parser::lexer::Lexer;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let _ = Lexer::with_str(input).collect();
+});
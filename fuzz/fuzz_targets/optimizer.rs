@@ -0,0 +1,24 @@
+#![no_main]
+
+use catastrophic_analyser::analyser::Analyser;
+use catastrophic_core::profiling::TimeKeeper;
+use catastrophic_hir_optimizer::optimizer::{Optimizer, Options};
+use catastrophic_parser::parser::Parser;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    let Ok(output) = Parser::with_str(input).parse() else {
+        return;
+    };
+
+    let Ok(hir) = Analyser::analyse_ast(output.ast) else {
+        return;
+    };
+
+    // Every pass checks the program is still well formed afterwards, as fuzz targets are built with debug assertions
+    let options = Options::all_passes();
+    let mut time_keeper = TimeKeeper::new(&"Fuzz");
+    if let Err(error) = Optimizer::optimize_hir(&options, hir, &mut time_keeper.scope(&"Optimization")) {
+        panic!("{error}");
+    }
+});
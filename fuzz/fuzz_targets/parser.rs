@@ -0,0 +1,11 @@
+#![no_main]
+
+use catastrophic_parser::parser::Parser;
+use libfuzzer_sys::fuzz_target;
+
+fuzz_target!(|input: &str| {
+    // Parsing permissively carries on past errors, which reaches more of the parser
+    let _ = Parser::with_str(input)
+        .permissive(true)
+        .parse();
+});
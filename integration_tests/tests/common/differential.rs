@@ -0,0 +1,342 @@
+//! Runs the same program through every backend and checks that they all behave the same way
+
+use std::{
+    fmt, fs,
+    io::Read,
+    path::{Path, PathBuf},
+    process::{Command, Output, Stdio},
+    sync::atomic::{AtomicUsize, Ordering},
+    thread,
+    time::{Duration, Instant},
+};
+
+use super::{get_llvm_binary, get_test_binary, TestBinary, LLVM_DIR};
+
+/// How long a backend may run a program for, as shrinking can easily make a program which never finishes
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backend {
+    /// `catastrophici`, running the program from HIR
+    Hir,
+    /// `catastrophici --opt all`, running the optimized program from MIR
+    Mir,
+    /// `catastrophicc --opt all`, running the compiled program with `lli`
+    Compiled,
+}
+
+/// Everything about a run of a program which should be the same whichever backend ran it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Outcome {
+    pub stdout: Vec<u8>,
+    /// The exit code, or nothing if the backend was killed by a signal
+    pub code: Option<i32>,
+    /// The context of the error reported, such as `Runtime error`, `panic` if the backend panicked, or `timeout` if it
+    /// didn't finish in time
+    pub error: Option<String>,
+}
+
+/// Backends which disagreed with the HIR interpreter on a program, after shrinking it as far as possible
+#[derive(Debug)]
+pub struct Mismatch {
+    pub program: String,
+    pub outcomes: Vec<(Backend, Outcome)>,
+}
+
+static NEXT_FILE: AtomicUsize = AtomicUsize::new(0);
+
+impl Backend {
+    /// The backends which can run here, as compiled code is only run when LLVM is installed
+    pub fn available() -> Vec<Backend> {
+        let llvm = std::env::var_os("LLVM_SYS_211_PREFIX").is_some() && LLVM_DIR.join("lli").exists();
+
+        if llvm {
+            vec![Backend::Hir, Backend::Mir, Backend::Compiled]
+        } else {
+            vec![Backend::Hir, Backend::Mir]
+        }
+    }
+
+    fn run(self, input: &Path, stdin: Option<&Path>) -> Outcome {
+        let mut command = match self {
+            Backend::Hir => get_test_binary(TestBinary::Interpreter),
+            Backend::Mir => {
+                let mut command = get_test_binary(TestBinary::Interpreter);
+                command.args(["--opt", "all"]);
+                command
+            }
+            Backend::Compiled => {
+                let mut command = get_test_binary(TestBinary::Compiler);
+                command
+                    .args(["--opt", "all"])
+                    .arg(input);
+
+                let Some(output) = run_with_timeout(&mut command) else {
+                    return Outcome::timeout();
+                };
+
+                if !output.status.success() {
+                    return Outcome::from_output(&output);
+                }
+
+                let llvm_output_path = input.with_extension("ll");
+                fs::write(&llvm_output_path, output.stdout).expect("Unable to write llvm output file");
+
+                let mut command = get_llvm_binary("lli");
+                command.arg(&llvm_output_path);
+
+                if let Some(stdin) = stdin {
+                    command.stdin(fs::File::open(stdin).expect("Unable to open stdin file"));
+                }
+
+                let output = run_with_timeout(&mut command);
+
+                fs::remove_file(llvm_output_path).expect("Unable to delete temporary file");
+
+                let Some(output) = output else {
+                    return Outcome::timeout();
+                };
+
+                return Outcome::from_compiled_output(&output);
+            }
+        };
+
+        if let Some(stdin) = stdin {
+            command.stdin(fs::File::open(stdin).expect("Unable to open stdin file"));
+        }
+
+        match run_with_timeout(command.arg(input)) {
+            Some(output) => Outcome::from_output(&output),
+            None => Outcome::timeout(),
+        }
+    }
+}
+
+/// Run a command to completion, or return nothing if it has to be killed for taking too long
+fn run_with_timeout(command: &mut Command) -> Option<Output> {
+    let mut child = command
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()
+        .expect("Unable to sucessfully run executable");
+
+    // The pipes are read as the program runs, so it never blocks on a full pipe
+    let read = |mut pipe: Box<dyn Read + Send>| {
+        thread::spawn(move || {
+            let mut buffer = Vec::new();
+            pipe.read_to_end(&mut buffer)
+                .expect("Unable to read output");
+            buffer
+        })
+    };
+
+    let stdout = read(Box::new(child.stdout.take().unwrap()));
+    let stderr = read(Box::new(child.stderr.take().unwrap()));
+
+    let deadline = Instant::now() + TIMEOUT;
+    let status = loop {
+        if let Some(status) = child
+            .try_wait()
+            .expect("Unable to wait for executable")
+        {
+            break Some(status);
+        }
+
+        if Instant::now() > deadline {
+            child
+                .kill()
+                .expect("Unable to kill executable");
+            child
+                .wait()
+                .expect("Unable to wait for executable");
+            break None;
+        }
+
+        thread::sleep(Duration::from_millis(10));
+    };
+
+    let (stdout, stderr) = (stdout.join().unwrap(), stderr.join().unwrap());
+
+    status.map(|status| Output { status, stdout, stderr })
+}
+
+impl Outcome {
+    fn timeout() -> Self {
+        Self {
+            stdout: Vec::new(),
+            code: None,
+            error: Some("timeout".to_owned()),
+        }
+    }
+
+    fn from_output(output: &Output) -> Self {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+
+        let error = stderr.lines().next().map(|line| {
+            if line.contains("panicked") {
+                "panic".to_owned()
+            } else {
+                line.trim_start_matches("Error: ")
+                    .to_owned()
+            }
+        });
+
+        Self {
+            stdout: output.stdout.clone(),
+            code: output.status.code(),
+            error,
+        }
+    }
+
+    /// A compiled program exits with whatever is left on top of its stack, which the interpreters don't report, and is
+    /// killed by a signal when it fails, without saying what went wrong. Both are normalised to how the interpreters
+    /// exit, with a code of zero once the program finishes and one with a runtime error if it fails.
+    fn from_compiled_output(output: &Output) -> Self {
+        let finished = output.status.code().is_some();
+
+        Self {
+            stdout: output.stdout.clone(),
+            code: Some(i32::from(!finished)),
+            error: (!finished).then(|| "Runtime error".to_owned()),
+        }
+    }
+}
+
+/// A temporary file holding a program, removed once it's no longer needed
+struct ProgramFile(PathBuf);
+
+impl ProgramFile {
+    fn new(program: &str) -> Self {
+        let path = std::env::temp_dir().join(format!(
+            "catastrophic_differential_{}_{}.cat",
+            std::process::id(),
+            NEXT_FILE.fetch_add(1, Ordering::Relaxed)
+        ));
+
+        fs::write(&path, program).expect("Unable to write program file");
+        Self(path)
+    }
+}
+
+impl Drop for ProgramFile {
+    fn drop(&mut self) {
+        let _ = fs::remove_file(&self.0);
+    }
+}
+
+/// Run a program through every available backend, returning their outcomes if any of them disagree
+fn compare(program: &str, stdin: Option<&Path>) -> Option<Vec<(Backend, Outcome)>> {
+    let file = ProgramFile::new(program);
+
+    let outcomes = Backend::available()
+        .into_iter()
+        .map(|backend| (backend, backend.run(&file.0, stdin)))
+        .collect::<Vec<_>>();
+
+    let (_, expected) = &outcomes[0];
+
+    outcomes
+        .iter()
+        .any(|(_, outcome)| outcome != expected)
+        .then_some(outcomes)
+}
+
+/// Split a program into pieces which can each be removed, keeping the whitespace after each one
+fn tokens(program: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut in_token = false;
+
+    for (index, c) in program.char_indices() {
+        if !c.is_whitespace() && !in_token && index > start {
+            tokens.push(&program[start..index]);
+            start = index;
+        }
+
+        in_token = !c.is_whitespace();
+    }
+
+    if start < program.len() {
+        tokens.push(&program[start..]);
+    }
+
+    tokens
+}
+
+/// Remove as much of a program as possible while it still fails the given check
+///
+/// Ever smaller runs of tokens are removed in turn, keeping any removal after which the check still fails
+pub fn shrink(program: &str, fails: impl Fn(&str) -> bool) -> String {
+    let mut tokens = tokens(program)
+        .into_iter()
+        .map(str::to_owned)
+        .collect::<Vec<_>>();
+
+    let mut size = tokens.len() / 2;
+
+    while size > 0 {
+        let mut start = 0;
+        let mut removed = false;
+
+        while start < tokens.len() {
+            let end = (start + size).min(tokens.len());
+            let candidate = [&tokens[..start], &tokens[end..]].concat();
+
+            if fails(&candidate.concat()) {
+                tokens = candidate;
+                removed = true;
+            } else {
+                start += size;
+            }
+        }
+
+        // Once nothing more can be removed at this size, try smaller pieces
+        if !removed {
+            size /= 2;
+        }
+    }
+
+    tokens.concat()
+}
+
+/// Check that every available backend agrees on a program, shrinking it if they don't
+///
+/// # Errors
+///
+/// Returns the smallest program found on which the backends still disagree
+pub fn check_program(program: &str, stdin: Option<&Path>) -> Result<(), Mismatch> {
+    if compare(program, stdin).is_none() {
+        return Ok(());
+    }
+
+    // Programs which don't finish are skipped over while shrinking, as how long each backend takes to give up on them
+    // has nothing to do with the original mismatch
+    let program = shrink(program, |candidate| {
+        compare(candidate, stdin).is_some_and(|outcomes| {
+            outcomes
+                .iter()
+                .all(|(_, outcome)| outcome != &Outcome::timeout())
+        })
+    });
+    let outcomes = compare(&program, stdin).expect("shrunk program no longer mismatches");
+
+    Err(Mismatch { program, outcomes })
+}
+
+impl fmt::Display for Mismatch {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "Backends disagree on program:\n{}\n", self.program)?;
+
+        for (backend, outcome) in &self.outcomes {
+            writeln!(
+                f,
+                "{backend:?}: code: {:?}, error: {:?}, stdout: {:?}",
+                outcome.code,
+                outcome.error,
+                String::from_utf8_lossy(&outcome.stdout)
+            )?;
+        }
+
+        Ok(())
+    }
+}
@@ -5,6 +5,8 @@ use std::path::{Path, PathBuf};
 use bintest::{BinTest, Command};
 use once_cell::sync::Lazy;
 
+pub mod differential;
+
 #[derive(Clone, Copy)]
 pub enum TestBinary {
     Compiler,
@@ -53,6 +55,7 @@ macro_rules! test_cases {
         test_cases!(fib_tail_recursive, $binary, $runner);
 
         test_cases!(error_unexpected_char, $binary, $runner);
+        test_cases!(error_integer_too_large, $binary, $runner);
         test_cases!(error_unmatched_open_brace, $binary, $runner);
         test_cases!(error_unmatched_close_brace, $binary, $runner);
         test_cases!(error_unterminated_string, $binary, $runner);
@@ -70,7 +73,9 @@ macro_rules! test_cases {
 }
 
 static TEST_CASE_DIR: Lazy<PathBuf> = Lazy::new(|| Path::new(std::env!("CARGO_MANIFEST_DIR")).join("test_cases"));
-static LLVM_DIR: Lazy<PathBuf> = Lazy::new(|| Path::new(std::env!("LLVM_SYS_211_PREFIX")).join("bin"));
+// Looked up when first needed rather than at compile time, so tests which don't use LLVM run without it
+static LLVM_DIR: Lazy<PathBuf> =
+    Lazy::new(|| Path::new(&std::env::var("LLVM_SYS_211_PREFIX").expect("LLVM_SYS_211_PREFIX must be set to run LLVM binaries")).join("bin"));
 
 pub fn get_test_case(binary: TestBinary, name: &str) -> TestCase {
     let test_case_path = TEST_CASE_DIR.join(name);
@@ -95,10 +100,14 @@ fn test_binary_name(binary: TestBinary) -> &'static str {
     }
 }
 
-fn get_test_binary(binary: TestBinary) -> Command {
+// Built once and shared, as the differential tests run the binaries many times over while shrinking
+static BIN_TEST: Lazy<BinTest> = Lazy::new(|| {
     BinTest::with()
         .workspace()
         .quiet()
         .build()
-        .command(test_binary_name(binary))
+});
+
+fn get_test_binary(binary: TestBinary) -> Command {
+    BIN_TEST.command(test_binary_name(binary))
 }
@@ -6,10 +6,18 @@ use common::{get_llvm_binary, get_test_case, TestBinary, TestCase};
 
 mod common;
 
-fn run_test_case(mut test_case: TestCase) {
+fn run_test_case(test_case: TestCase) {
+    run_compiled_test_case(test_case, "none");
+}
+
+fn run_optimized_test_case(test_case: TestCase) {
+    run_compiled_test_case(test_case, "all");
+}
+
+fn run_compiled_test_case(mut test_case: TestCase, opt: &str) {
     let llvm_output_path = test_case
         .input
-        .with_file_name("compiler_llvm_output");
+        .with_file_name(format!("compiler_{opt}_llvm_output"));
 
     let mut lli_command = get_llvm_binary("lli");
 
@@ -20,7 +28,7 @@ fn run_test_case(mut test_case: TestCase) {
     // First, run the `catastrophicc` compiler
     let compiler_output = test_case
         .command
-        .args(["--opt", "none"])
+        .args(["--opt", opt])
         .arg(test_case.input)
         .stdout(fs::File::create(&llvm_output_path).expect("Unable to open llvm output file"))
         .output()
@@ -47,3 +55,12 @@ mod compiler {
 
     test_cases!(Compiler, run_test_case);
 }
+
+mod optimized_compiler {
+    use super::*;
+
+    test_cases!(Compiler, run_optimized_test_case);
+
+    // Only run where tail calls are eliminated, as unoptimized compiled code recurses once per iteration
+    test_cases!(tail_call_loop, Compiler, run_optimized_test_case);
+}
@@ -0,0 +1,27 @@
+#![cfg(not(tarpaulin))]
+
+use common::{get_test_case, TestBinary, TestCase};
+
+mod common;
+
+fn run_test_case(mut test_case: TestCase) {
+    let command = test_case.command.arg(test_case.input);
+
+    // Run the `catastrophicc` compiler twice on the same input
+    let first = command
+        .output()
+        .expect("Unable to sucessfully run executable");
+
+    let second = command
+        .output()
+        .expect("Unable to sucessfully run executable");
+
+    assert_eq!(first.stdout, second.stdout);
+    assert_eq!(first.stderr, second.stderr);
+}
+
+mod determinism {
+    use super::*;
+
+    test_cases!(Compiler, run_test_case);
+}
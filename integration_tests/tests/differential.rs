@@ -0,0 +1,109 @@
+#![cfg(not(tarpaulin))]
+
+use std::fs;
+
+use catastrophic_test_support::generator::{Config, Program};
+use common::{
+    differential::{check_program, shrink},
+    get_test_case, TestBinary, TestCase,
+};
+
+mod common;
+
+fn run_test_case(test_case: TestCase) {
+    let program = fs::read_to_string(test_case.input).expect("Unable to read input");
+    let stdin = test_case
+        .stdin
+        .exists()
+        .then_some(test_case.stdin.as_path());
+
+    if let Err(mismatch) = check_program(&program, stdin) {
+        panic!("{mismatch}");
+    }
+}
+
+fn check(program: &str) {
+    if let Err(mismatch) = check_program(program, None) {
+        panic!("{mismatch}");
+    }
+}
+
+mod differential {
+    use super::*;
+
+    test_cases!(Interpreter, run_test_case);
+
+    test_cases!(tail_call_loop, Interpreter, run_test_case);
+
+    #[test]
+    fn closures_capture_arguments() {
+        check("adder: x -> { y -> { x y + () } }\n3 adder () 4 swap: a -> b -> { a b } () () .");
+    }
+
+    #[test]
+    fn conditional_calls() {
+        check("choose: x -> { { 1 . } { 2 . } x ? () () }\n0 choose () 1 choose () 5 choose ()");
+    }
+
+    #[test]
+    fn value_left_on_stack() {
+        // Compiled code exits with this value, which the interpreters don't
+        check("1 2 + ()");
+    }
+
+    #[test]
+    fn runtime_errors() {
+        check("1 ()");
+        check("x: { } ()");
+        check("{ } .");
+    }
+
+    // Catastrophic has no negative literals, so these are worked out by the program
+    const NEGATIVE_ONE: &str = "1 0 - ()";
+    const MIN: &str = "1 9223372036854775807 0 - () - ()";
+
+    #[test]
+    fn arithmetic_errors() {
+        check("0 1 / () .");
+        check("9223372036854775807 1 + () .");
+        check(&format!("1 {MIN} - () ."));
+        check("2 4611686018427387904 * () .");
+        check(&format!("{NEGATIVE_ONE} {MIN} / () ."));
+        check(&format!("{NEGATIVE_ONE} {MIN} * () ."));
+        check(&format!("{MIN} {NEGATIVE_ONE} * () ."));
+        check("ignore: x -> { 1 . }\n0 1 / () ignore ()");
+        check("double: x -> { x x + () . }\n9223372036854775807 1 + () double ()");
+    }
+
+    #[test]
+    fn arithmetic_near_limits() {
+        check(&format!("{MIN} ."));
+        check(&format!("9223372036854775807 {NEGATIVE_ONE} * () ."));
+        check(&format!("2 {MIN} / () ."));
+        check("2 4611686018427387903 * () .");
+    }
+
+    #[test]
+    fn generated_programs() {
+        // Every backend runs each program as a separate process, so fewer seeds are tried than in the library tests
+        let config = Config::default().with_budget(1000);
+
+        for seed in 0..50 {
+            check(&Program::generate(seed, &config).to_string());
+        }
+    }
+}
+
+#[test]
+fn shrink_to_failing_part() {
+    let program = "1 2 + () .\n{ 3 } () .\n4 5 - () .";
+
+    assert_eq!(shrink(program, |candidate| candidate.contains("3 }")).trim(), "3 }");
+}
+
+#[test]
+fn shrink_keeps_failing_program() {
+    let program = "a b c d e f";
+
+    assert_eq!(shrink(program, |candidate| candidate.contains('b') && candidate.contains('e')), "b e ");
+}
@@ -0,0 +1,37 @@
+#![cfg(not(tarpaulin))]
+
+use std::fs;
+
+use common::{get_test_case, TestBinary, TestCase};
+
+mod common;
+
+// Run from the test case's directory, so the source links in the output don't depend on where the repository is
+fn run_test_case(mut test_case: TestCase) {
+    let doc_output_path = test_case
+        .input
+        .with_file_name("doc_output");
+
+    let output = test_case
+        .command
+        .current_dir(test_case.input.parent().unwrap())
+        .args(["doc", "--output"])
+        .arg(&doc_output_path)
+        .arg(test_case.input.file_name().unwrap())
+        .output()
+        .expect("Unable to sucessfully run executable");
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+
+    let doc = fs::read(&doc_output_path).expect("Unable to read doc output");
+    fs::remove_file(doc_output_path).expect("Unable to delete temporary file");
+
+    assert_eq!(doc, fs::read(test_case.expected).expect("Unable to read expected output"));
+}
+
+mod doc {
+    use super::*;
+
+    // Refers to a symbol it doesn't define, so would fail to compile
+    test_cases!(doc_library, Compiler, run_test_case);
+}
@@ -30,4 +30,44 @@ mod interpreter {
     use super::*;
 
     test_cases!(Interpreter, run_test_case);
+
+    // Only run where tail calls are eliminated, as unoptimized compiled code recurses once per iteration
+    test_cases!(tail_call_loop, Interpreter, run_test_case);
+
+    // Only run by the interpreters, as compiled code uses the native stack for calls
+    test_cases!(deep_recursion, Interpreter, run_test_case);
+
+    // Compiled code stops without saying what went wrong, so only the differential tests check it fails on these too
+    test_cases!(error_division_by_zero, Interpreter, run_test_case);
+    test_cases!(error_overflow, Interpreter, run_test_case);
+    test_cases!(error_empty_random_range, Interpreter, run_test_case);
+
+    #[test]
+    fn max_depth_exceeded() {
+        let mut test_case = get_test_case(TestBinary::Interpreter, "deep_recursion");
+
+        let output = test_case
+            .command
+            .args(["--max-depth", "100"])
+            .arg(test_case.input)
+            .output()
+            .expect("Unable to sucessfully run executable");
+
+        assert!(!output.status.success());
+        assert!(String::from_utf8_lossy(&output.stderr).contains("more than 100 calls deep"));
+    }
+
+    #[test]
+    fn overflow_wrapping() {
+        let mut test_case = get_test_case(TestBinary::Interpreter, "overflow_wrapping");
+
+        let output = test_case
+            .command
+            .args(["--overflow", "wrap"])
+            .arg(test_case.input)
+            .output()
+            .expect("Unable to sucessfully run executable");
+
+        assert_eq!(output.stdout, fs::read(test_case.expected).expect("Unable to read expected output"));
+    }
 }
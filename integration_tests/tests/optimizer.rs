@@ -2,48 +2,38 @@
 
 use std::fs;
 
-use common::{get_llvm_binary, get_test_case, TestBinary, TestCase};
+use common::{get_test_case, TestBinary, TestCase};
 
 mod common;
 
+// Optimized programs are run by the interpreter from MIR, so these don't need LLVM
 fn run_test_case(mut test_case: TestCase) {
-    let llvm_output_path = test_case
-        .input
-        .with_file_name("optimizer_llvm_output");
-
-    let mut lli_command = get_llvm_binary("lli");
-
     if let Ok(stdin) = fs::File::open(test_case.stdin) {
-        lli_command.stdin(stdin);
+        test_case.command.stdin(stdin);
     }
 
-    // First, run the `catastrophicc` compiler
-    let compiler_output = test_case
+    let output = test_case
         .command
         .args(["--opt", "all"])
         .arg(test_case.input)
-        .stdout(fs::File::create(&llvm_output_path).expect("Unable to open llvm output file"))
         .output()
         .expect("Unable to sucessfully run executable");
 
-    if let Ok(expected_stderr) = fs::read(test_case.stderr) {
-        // If we are testing for an error, check that now and return if so
-        assert_eq!(compiler_output.stderr, expected_stderr);
+    let (actual, expected) = if let Ok(expected_stderr) = fs::read(test_case.stderr) {
+        (output.stderr, expected_stderr)
     } else {
-        // Otherwise, run `lli` on the `catastrphicc` output
-        let output = lli_command
-            .arg(&llvm_output_path)
-            .output()
-            .expect("Unable to sucessfully run lli");
-
-        assert_eq!(output.stdout, fs::read(test_case.expected).expect("Unable to read expected output"));
-    }
+        (output.stdout, fs::read(test_case.expected).expect("Unable to read expected output"))
+    };
 
-    fs::remove_file(llvm_output_path).expect("Unable to delete temporary file");
+    assert_eq!(actual, expected);
 }
 
 mod optimizer {
     use super::*;
 
-    test_cases!(Compiler, run_test_case);
+    test_cases!(Interpreter, run_test_case);
+
+    test_cases!(tail_call_loop, Interpreter, run_test_case);
+
+    test_cases!(deep_recursion, Interpreter, run_test_case);
 }
@@ -0,0 +1,42 @@
+#![cfg(not(tarpaulin))]
+
+use std::fs;
+
+use common::{get_test_case, TestBinary, TestCase};
+
+mod common;
+
+// Renaming rewrites the input in place, so it's done to a copy
+fn run_rename_test_case(mut test_case: TestCase, location: &str, name: &str) {
+    let path = std::env::temp_dir().join(format!("catastrophic_rename_{}_{}.cat", std::process::id(), location.replace(':', "_")));
+
+    fs::copy(&test_case.input, &path).expect("Unable to copy input");
+
+    let output = test_case
+        .command
+        .args(["--rename", location, "--to", name])
+        .arg(&path)
+        .output()
+        .expect("Unable to sucessfully run executable");
+
+    let renamed = fs::read_to_string(&path).expect("Unable to read renamed input");
+    fs::remove_file(&path).expect("Unable to delete temporary file");
+
+    assert!(output.status.success(), "{}", String::from_utf8_lossy(&output.stderr));
+    assert_eq!(renamed, fs::read_to_string(test_case.expected).expect("Unable to read expected output"));
+}
+
+mod refactoring {
+    use super::*;
+
+    // Positions count from 0, as error messages show them
+    #[test]
+    fn rename_from_definition() {
+        run_rename_test_case(get_test_case(TestBinary::Compiler, "rename_symbol"), "0:0", "show");
+    }
+
+    #[test]
+    fn rename_from_use() {
+        run_rename_test_case(get_test_case(TestBinary::Compiler, "rename_symbol"), "1:2", "show");
+    }
+}
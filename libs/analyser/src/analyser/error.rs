@@ -3,12 +3,12 @@ use catastrophic_core::{
     span::Span,
 };
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub struct CompileErrors {
     pub errors: Vec<CompileError>,
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum CompileError {
     UndefinedSymbolError(Span<String>),
 }
@@ -3,9 +3,13 @@ use catastrophic_hir::hir;
 
 use self::state::State;
 
-pub use self::error::CompileErrors;
+pub use self::{
+    error::{CompileError, CompileErrors},
+    output::AnalysisOutput,
+};
 
 mod error;
+mod output;
 mod state;
 mod test;
 
@@ -13,6 +17,17 @@ pub struct Analyser;
 
 impl Analyser {
     pub fn analyse_ast(top_level: ast::Block) -> Result<Vec<hir::Block>, CompileErrors> {
+        let output = Self::analyse_ast_permissive(top_level);
+
+        if output.errors.is_empty() {
+            Ok(output.ir)
+        } else {
+            Err(output.errors.into())
+        }
+    }
+
+    #[must_use]
+    pub fn analyse_ast_permissive(top_level: ast::Block) -> AnalysisOutput {
         State::new(top_level).analyse()
     }
 }
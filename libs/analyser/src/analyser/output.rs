@@ -0,0 +1,11 @@
+use catastrophic_hir::hir;
+
+use crate::index::CrossReferences;
+
+use super::error::CompileError;
+
+pub struct AnalysisOutput {
+    pub ir: Vec<hir::Block>,
+    pub references: CrossReferences,
+    pub errors: Vec<CompileError>,
+}
@@ -1,19 +1,26 @@
-use std::collections::VecDeque;
+use std::collections::{BTreeMap, VecDeque};
 
 use catastrophic_ast::ast;
+use catastrophic_core::span::Span;
 use catastrophic_hir::hir;
 
-use super::error::{CompileError, CompileErrors};
+use crate::index::{CrossReferences, Definition, DefinitionKind, Use};
+
+use super::{error::CompileError, output::AnalysisOutput};
+
+type Scope = BTreeMap<String, Span<()>>;
 
 pub struct QueuedBlock {
     block: ast::Block,
     name: String,
-    parent: Option<usize>,
+    source: hir::BlockSource,
 }
 
 pub struct State {
     queue: VecDeque<QueuedBlock>,
     ir: Vec<hir::Block>,
+    scopes: Vec<Scope>,
+    references: CrossReferences,
     errors: Vec<CompileError>,
 }
 
@@ -22,15 +29,15 @@ impl QueuedBlock {
         Self {
             block,
             name: name.into(),
-            parent: None,
+            source: hir::BlockSource::default(),
         }
     }
 
-    pub fn new_with_parent<S: Into<String>>(block: ast::Block, name: S, parent: usize) -> Self {
+    pub fn new_with_source<S: Into<String>>(block: ast::Block, name: S, source: hir::BlockSource) -> Self {
         Self {
             block,
             name: name.into(),
-            parent: Some(parent),
+            source,
         }
     }
 }
@@ -40,30 +47,54 @@ impl State {
         Self {
             queue: VecDeque::from([QueuedBlock::new(top_level, "start")]),
             ir: Vec::new(),
+            scopes: Vec::new(),
+            references: CrossReferences::default(),
             errors: Vec::new(),
         }
     }
 
-    fn queue_block<S: Into<String>>(&mut self, block: ast::Block, name: S, parent: usize) -> usize {
+    fn queue_block<S: Into<String>>(&mut self, block: ast::Block, name: S, source: hir::BlockSource) -> usize {
         self.queue
-            .push_front(QueuedBlock::new_with_parent(block, name, parent));
+            .push_front(QueuedBlock::new_with_source(block, name, source));
         self.queue.len() + self.ir.len()
     }
 
-    fn analyse_block(&mut self, block: QueuedBlock, index: usize) -> hir::Block {
+    fn define(&mut self, scope: &mut Scope, name: Span<String>, kind: DefinitionKind, index: usize) {
+        scope.insert(name.data.clone(), name.swap(()));
+        self.references
+            .define(Definition::new(name, kind, index));
+    }
+
+    fn analyse_block(&mut self, block: QueuedBlock, index: usize) -> (hir::Block, Scope) {
+        let mut scope = block
+            .source
+            .parent
+            .map(|index| self.scopes[index].clone())
+            .unwrap_or_default();
+
+        for arg in &block.block.args {
+            self.define(&mut scope, arg.clone(), DefinitionKind::Arg, index);
+        }
+
         let mut ir = hir::Block::new(
             block.block.args,
             block
+                .source
                 .parent
                 .map(|index| &self.ir[index]),
             block.name,
-        );
+        )
+        .with_source(block.source);
 
         for (name, symbol) in block.block.symbols {
+            self.define(&mut scope, symbol.name_span.swap(name.clone()), DefinitionKind::Label, index);
+
+            let value_span = symbol.value.swap(());
             let symbol = match symbol.value.data {
                 ast::SymbolValue::Number(value) => hir::Value::Number(value),
                 ast::SymbolValue::Block(block) => {
-                    hir::Value::Function(hir::Function::Block(self.queue_block(block, format!("{}_{}", ir.name, name), index)))
+                    let source = hir::BlockSource::new(index, value_span, Some(name.clone()));
+                    hir::Value::Function(hir::Function::Block(self.queue_block(block, format!("{}_{}", ir.name, name), source)))
                 }
                 ast::SymbolValue::Builtin(builtin) => hir::Value::Function(hir::Function::Builtin(builtin)),
             };
@@ -82,11 +113,22 @@ impl State {
                         ast::InstrValue::Number(value) => hir::Value::Number(value),
                         ast::InstrValue::Block(block) => {
                             next += 1u64;
-                            hir::Value::Function(hir::Function::Block(self.queue_block(block, format!("{}_{}", ir.name, next), index)))
+                            let source = hir::BlockSource::new(index, instr_span, None);
+                            hir::Value::Function(hir::Function::Block(self.queue_block(block, format!("{}_{}", ir.name, next), source)))
                         }
                         ast::InstrValue::Builtin(builtin) => hir::Value::Function(hir::Function::Builtin(builtin)),
                         ast::InstrValue::Ident(ref name) => {
                             if let Some(value) = ir.lookup_symbol(name) {
+                                if let Some(definition) = scope.get(name) {
+                                    self.references.add_use(
+                                        *definition,
+                                        Use {
+                                            span: instr_span,
+                                            block: index,
+                                        },
+                                    );
+                                }
+
                                 value
                             } else {
                                 self.errors
@@ -102,19 +144,20 @@ impl State {
             ir.push_instr(instr_span.swap(instr));
         }
 
-        ir
+        (ir, scope)
     }
 
-    pub fn analyse(mut self) -> Result<Vec<hir::Block>, CompileErrors> {
+    pub fn analyse(mut self) -> AnalysisOutput {
         while let Some(block) = self.queue.pop_back() {
-            let ir = self.analyse_block(block, self.ir.len());
+            let (ir, scope) = self.analyse_block(block, self.ir.len());
             self.ir.push(ir);
+            self.scopes.push(scope);
         }
 
-        if self.errors.is_empty() {
-            Ok(self.ir)
-        } else {
-            Err(self.errors.into())
+        AnalysisOutput {
+            ir: self.ir,
+            references: self.references,
+            errors: self.errors,
         }
     }
 }
@@ -1,6 +1,8 @@
 #![cfg(test)]
 
 use catastrophic_core::span::{Location, Span};
+use catastrophic_parser::parser::Parser;
+use catastrophic_test_support::generator::{Config, Program};
 
 use crate::analyser::error::CompileError;
 
@@ -225,12 +227,35 @@ fn analyse_symbol_block() {
     let mut expected1 = hir::Block::new(vec![], None, "start");
     expected1.push_symbol("sym".to_string(), hir::Value::Function(hir::Function::Block(1)));
 
-    let mut expected2 = hir::Block::new(vec![], None, "start_sym");
+    let mut expected2 =
+        hir::Block::new(vec![], None, "start_sym").with_source(hir::BlockSource::new(0, span((), 0, 4, 0, 6), Some("sym".to_string())));
     expected2.push_symbol("sym".to_string(), hir::Value::Function(hir::Function::Block(1)));
 
     analyser_test(input, &[expected1, expected2]);
 }
 
+#[test]
+fn analyse_symbol_blocks_in_source_order() {
+    let mut input = ast::Block::no_args();
+    input
+        .with_symbol("b".to_string())
+        .or_insert_with(|| ast::Symbol::new(span((), 0, 0, 0, 1), span(ast::SymbolValue::Block(ast::Block::no_args()), 0, 3, 0, 5)));
+    input
+        .with_symbol("a".to_string())
+        .or_insert_with(|| ast::Symbol::new(span((), 1, 0, 1, 1), span(ast::SymbolValue::Block(ast::Block::no_args()), 1, 3, 1, 5)));
+
+    let mut expected1 = hir::Block::new(vec![], None, "start");
+    expected1.push_symbol("b".to_string(), hir::Value::Function(hir::Function::Block(1)));
+    expected1.push_symbol("a".to_string(), hir::Value::Function(hir::Function::Block(2)));
+
+    let expected2 =
+        hir::Block::new(vec![], Some(&expected1), "start_b").with_source(hir::BlockSource::new(0, span((), 0, 3, 0, 5), Some("b".to_string())));
+    let expected3 =
+        hir::Block::new(vec![], Some(&expected1), "start_a").with_source(hir::BlockSource::new(0, span((), 1, 3, 1, 5), Some("a".to_string())));
+
+    analyser_test(input, &[expected1, expected2, expected3]);
+}
+
 #[test]
 fn analyse_empty_block() {
     let block = ast::Block::no_args();
@@ -241,7 +266,7 @@ fn analyse_empty_block() {
     let mut expected1 = hir::Block::new(vec![], None, "start");
     expected1.push_instr(span(hir::Instr::Push(hir::Value::Function(hir::Function::Block(1))), 0, 0, 0, 2));
 
-    let expected2 = hir::Block::new(vec![], Some(&expected1), "start_1");
+    let expected2 = hir::Block::new(vec![], Some(&expected1), "start_1").with_source(hir::BlockSource::new(0, span((), 0, 0, 0, 2), None));
 
     analyser_test(input, &[expected1, expected2]);
 }
@@ -271,7 +296,11 @@ fn analyse_arg_block() {
     let mut expected1 = hir::Block::new(vec![], None, "start");
     expected1.push_instr(span(hir::Instr::Push(hir::Value::Function(hir::Function::Block(1))), 0, 0, 2, 1));
 
-    let expected2 = hir::Block::new(vec![span("arg".to_string(), 0, 0, 0, 3)], Some(&expected1), "start_1");
+    let expected2 = hir::Block::new(vec![span("arg".to_string(), 0, 0, 0, 3)], Some(&expected1), "start_1").with_source(hir::BlockSource::new(
+        0,
+        span((), 0, 0, 2, 1),
+        None,
+    ));
 
     analyser_test(input, &[expected1, expected2]);
 }
@@ -287,7 +316,11 @@ fn analyse_arg_block_and_valid_ident() {
     let mut expected1 = hir::Block::new(vec![], None, "start");
     expected1.push_instr(span(hir::Instr::Push(hir::Value::Function(hir::Function::Block(1))), 0, 0, 2, 1));
 
-    let mut expected2 = hir::Block::new(vec![span("arg".to_string(), 0, 0, 0, 3)], Some(&expected1), "start_1");
+    let mut expected2 = hir::Block::new(vec![span("arg".to_string(), 0, 0, 0, 3)], Some(&expected1), "start_1").with_source(hir::BlockSource::new(
+        0,
+        span((), 0, 0, 2, 1),
+        None,
+    ));
     expected2.push_instr(span(hir::Instr::Push(hir::Value::Arg(0)), 1, 1, 1, 4));
 
     analyser_test(input, &[expected1, expected2]);
@@ -308,7 +341,7 @@ fn analyse_block_and_valid_parent_symbol() {
     expected1.push_symbol("sym".to_string(), hir::Value::Number(24680));
     expected1.push_instr(span(hir::Instr::Push(hir::Value::Function(hir::Function::Block(1))), 2, 0, 4, 1));
 
-    let mut expected2 = hir::Block::new(vec![], Some(&expected1), "start_1");
+    let mut expected2 = hir::Block::new(vec![], Some(&expected1), "start_1").with_source(hir::BlockSource::new(0, span((), 2, 0, 4, 1), None));
     expected2.push_instr(span(hir::Instr::Push(hir::Value::Number(24680)), 3, 1, 3, 4));
 
     analyser_test(input, &[expected1, expected2]);
@@ -328,3 +361,18 @@ fn analyse_undefined_symbol_fails() {
         })
     );
 }
+
+#[test]
+fn analyse_generated_programs() {
+    let config = Config::default().with_random(true);
+
+    for seed in 0..200 {
+        let program = Program::generate(seed, &config);
+        let ast = Parser::with_str(&program.to_string())
+            .parse()
+            .unwrap()
+            .ast;
+
+        assert!(Analyser::analyse_ast(ast).is_ok(), "seed {seed}:\n{program}");
+    }
+}
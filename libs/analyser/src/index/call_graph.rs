@@ -0,0 +1,97 @@
+use std::collections::BTreeSet;
+
+use catastrophic_hir::hir;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EdgeKind {
+    Reference,
+    Call,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Edge {
+    pub target: usize,
+    pub kind: EdgeKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CallGraph {
+    edges: Vec<BTreeSet<Edge>>,
+}
+
+impl CallGraph {
+    /// Build the static call graph of a program, where pushing a block followed immediately by a
+    /// call is a direct call, and pushing a block anywhere else is a reference to it
+    #[must_use]
+    pub fn from_hir(blocks: &[hir::Block]) -> Self {
+        let edges = blocks
+            .iter()
+            .map(|block| {
+                let mut edges = BTreeSet::new();
+                let mut instrs = block.instrs.iter().peekable();
+
+                while let Some(instr) = instrs.next() {
+                    if let hir::Instr::Push(hir::Value::Function(hir::Function::Block(target))) = instr.data {
+                        let kind = match instrs.peek().map(|next| next.data) {
+                            Some(hir::Instr::Command(hir::Command::Call)) => EdgeKind::Call,
+                            _ => EdgeKind::Reference,
+                        };
+
+                        edges.insert(Edge { target, kind });
+                    }
+                }
+
+                edges
+            })
+            .collect();
+
+        Self { edges }
+    }
+
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.edges.len()
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.edges.is_empty()
+    }
+
+    pub fn edges(&self, block: usize) -> impl Iterator<Item = Edge> + '_ {
+        self.edges
+            .get(block)
+            .into_iter()
+            .flatten()
+            .copied()
+    }
+
+    pub fn callees(&self, block: usize) -> impl Iterator<Item = usize> + '_ {
+        self.edges(block)
+            .filter(|edge| edge.kind == EdgeKind::Call)
+            .map(|edge| edge.target)
+    }
+
+    #[must_use]
+    pub fn callers(&self, block: usize) -> BTreeSet<usize> {
+        self.sources(block, |edge| edge.kind == EdgeKind::Call)
+    }
+
+    #[must_use]
+    pub fn referrers(&self, block: usize) -> BTreeSet<usize> {
+        self.sources(block, |_| true)
+    }
+
+    fn sources(&self, block: usize, filter: impl Fn(&Edge) -> bool) -> BTreeSet<usize> {
+        self.edges
+            .iter()
+            .enumerate()
+            .filter(|(_, edges)| {
+                edges
+                    .iter()
+                    .any(|edge| edge.target == block && filter(edge))
+            })
+            .map(|(source, _)| source)
+            .collect()
+    }
+}
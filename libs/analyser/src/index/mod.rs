@@ -0,0 +1,8 @@
+pub use self::{
+    call_graph::{CallGraph, Edge, EdgeKind},
+    references::{CrossReferences, Definition, DefinitionKind, Use},
+};
+
+mod call_graph;
+mod references;
+mod test;
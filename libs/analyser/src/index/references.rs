@@ -0,0 +1,94 @@
+use std::collections::BTreeMap;
+
+use catastrophic_core::span::{Location, Span};
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub enum DefinitionKind {
+    Label,
+    Arg,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Use {
+    pub span: Span<()>,
+    pub block: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Definition {
+    pub name: Span<String>,
+    pub kind: DefinitionKind,
+    pub block: usize,
+    pub uses: Vec<Use>,
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CrossReferences {
+    definitions: BTreeMap<Span<()>, Definition>,
+}
+
+fn contains(span: Span<()>, location: Location) -> bool {
+    span.start <= location && location < span.end
+}
+
+impl Definition {
+    #[must_use]
+    pub fn new(name: Span<String>, kind: DefinitionKind, block: usize) -> Self {
+        Self {
+            name,
+            kind,
+            block,
+            uses: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn span(&self) -> Span<()> {
+        self.name.swap(())
+    }
+}
+
+impl CrossReferences {
+    pub(crate) fn define(&mut self, definition: Definition) {
+        self.definitions
+            .insert(definition.span(), definition);
+    }
+
+    pub(crate) fn add_use(&mut self, definition: Span<()>, reference: Use) {
+        if let Some(definition) = self.definitions.get_mut(&definition) {
+            definition.uses.push(reference);
+        }
+    }
+
+    pub fn definitions(&self) -> impl Iterator<Item = &Definition> {
+        self.definitions.values()
+    }
+
+    #[must_use]
+    pub fn definition(&self, span: Span<()>) -> Option<&Definition> {
+        self.definitions.get(&span)
+    }
+
+    #[must_use]
+    pub fn uses(&self, span: Span<()>) -> &[Use] {
+        self.definition(span)
+            .map_or(&[], |definition| &definition.uses)
+    }
+
+    /// Find the definition whose name, or one of whose uses, covers the given location
+    #[must_use]
+    pub fn definition_at(&self, location: Location) -> Option<&Definition> {
+        self.definitions().find(|definition| {
+            contains(definition.span(), location)
+                || definition
+                    .uses
+                    .iter()
+                    .any(|reference| contains(reference.span, location))
+        })
+    }
+
+    pub fn definitions_named<'a>(&'a self, name: &'a str) -> impl Iterator<Item = &'a Definition> {
+        self.definitions()
+            .filter(move |definition| definition.name.data == name)
+    }
+}
@@ -0,0 +1,118 @@
+#![cfg(test)]
+
+use std::collections::BTreeSet;
+
+use catastrophic_core::span::{Location, Span};
+use catastrophic_parser::parser::Parser;
+
+use crate::analyser::{Analyser, AnalysisOutput};
+
+use super::*;
+
+fn span(from_line: usize, from_col: usize, to_line: usize, to_col: usize) -> Span<()> {
+    Span::new(Location::new(from_line, from_col), Location::new(to_line, to_col), ())
+}
+
+fn analyse(input: &str) -> AnalysisOutput {
+    let ast = Parser::with_str(input)
+        .parse()
+        .unwrap()
+        .ast;
+
+    Analyser::analyse_ast_permissive(ast)
+}
+
+fn use_spans(output: &AnalysisOutput, definition: Span<()>) -> Vec<Span<()>> {
+    output
+        .references
+        .uses(definition)
+        .iter()
+        .map(|reference| reference.span)
+        .collect()
+}
+
+#[test]
+fn references_label_uses() {
+    let output = analyse("a: 5\na a +");
+
+    let definition = output
+        .references
+        .definition(span(0, 0, 0, 1))
+        .unwrap();
+
+    assert_eq!(definition.name.data, "a");
+    assert_eq!(definition.kind, DefinitionKind::Label);
+    assert_eq!(use_spans(&output, span(0, 0, 0, 1)), [span(1, 0, 1, 1), span(1, 2, 1, 3)]);
+}
+
+#[test]
+fn references_arg_uses() {
+    let output = analyse("x -> { x x }");
+
+    let definition = output
+        .references
+        .definition(span(0, 0, 0, 1))
+        .unwrap();
+
+    assert_eq!(definition.kind, DefinitionKind::Arg);
+    assert_eq!(definition.block, 1);
+    assert_eq!(use_spans(&output, span(0, 0, 0, 1)), [span(0, 7, 0, 8), span(0, 9, 0, 10)]);
+}
+
+#[test]
+fn references_respect_shadowing() {
+    let output = analyse("x: 1\nx -> { x }\nx");
+
+    assert_eq!(use_spans(&output, span(0, 0, 0, 1)), [span(2, 0, 2, 1)]);
+    assert_eq!(use_spans(&output, span(1, 0, 1, 1)), [span(1, 7, 1, 8)]);
+}
+
+#[test]
+fn references_nested_uses() {
+    let output = analyse("f: { g () }\ng: { 1 }");
+
+    let uses = output.references.uses(span(1, 0, 1, 1));
+    assert_eq!(
+        uses,
+        [Use {
+            span: span(0, 5, 0, 6),
+            block: 1
+        }]
+    );
+}
+
+#[test]
+fn references_definition_at_location() {
+    let output = analyse("value: 5\nvalue .");
+
+    let from_use = output
+        .references
+        .definition_at(Location::new(1, 3))
+        .unwrap();
+
+    assert_eq!(from_use.span(), span(0, 0, 0, 5));
+    assert!(output
+        .references
+        .definition_at(Location::new(1, 6))
+        .is_none());
+}
+
+#[test]
+fn call_graph_calls_and_references() {
+    let output = analyse("f: { g () }\ng: { f }\nf ()");
+    let graph = CallGraph::from_hir(&output.ir);
+
+    assert_eq!(graph.len(), 3);
+    assert_eq!(graph.callees(0).collect::<Vec<_>>(), [1]);
+    assert_eq!(graph.callees(1).collect::<Vec<_>>(), [2]);
+    assert_eq!(graph.callees(2).count(), 0);
+    assert_eq!(
+        graph.edges(2).collect::<Vec<_>>(),
+        [Edge {
+            target: 1,
+            kind: EdgeKind::Reference
+        }]
+    );
+    assert_eq!(graph.callers(1), BTreeSet::from([0]));
+    assert_eq!(graph.referrers(1), BTreeSet::from([0, 2]));
+}
@@ -1,4 +1,6 @@
 pub type Error = analyser::CompileErrors;
 
 pub mod analyser;
+pub mod index;
+pub mod rename;
 pub mod stage;
@@ -0,0 +1,43 @@
+use catastrophic_core::{
+    error::{context::ErrorProvider, writer::ErrorWriter},
+    span::{Location, Span},
+};
+
+use crate::analyser::CompileErrors;
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RenameError {
+    CompileErrors(CompileErrors),
+    NoSymbol(Location),
+    DuplicateSymbol { existing: Span<()>, renamed: Span<()> },
+    CapturedReference { reference: Span<()>, definition: Span<()> },
+}
+
+impl From<CompileErrors> for RenameError {
+    fn from(errors: CompileErrors) -> Self {
+        Self::CompileErrors(errors)
+    }
+}
+
+impl ErrorProvider for RenameError {
+    fn write_errors(&self, writer: &mut dyn ErrorWriter) -> std::fmt::Result {
+        match self {
+            RenameError::CompileErrors(errors) => errors.write_errors(writer)?,
+            RenameError::NoSymbol(location) => {
+                let mut end = *location;
+                end.advance();
+                writer.error(Some(Span::new(*location, end, ())), "No symbol to rename at this location")?;
+            }
+            RenameError::DuplicateSymbol { existing, renamed } => {
+                writer.error(Some(*renamed), "Renaming this symbol would duplicate an existing definition")?;
+                writer.note(*existing, "Symbol with the new name is already defined here:")?;
+            }
+            RenameError::CapturedReference { reference, definition } => {
+                writer.error(Some(*reference), "Renaming would change which symbol this refers to")?;
+                writer.note(*definition, "It would instead refer to this definition:")?;
+            }
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,176 @@
+use std::collections::BTreeMap;
+
+use catastrophic_core::span::{Location, Span};
+
+use crate::{
+    analyser::AnalysisOutput,
+    index::{Definition, DefinitionKind},
+};
+
+pub use self::error::RenameError;
+
+mod error;
+mod test;
+
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Edit {
+    pub span: Span<()>,
+    pub text: String,
+}
+
+struct Scopes<'a> {
+    analysis: &'a AnalysisOutput,
+    blocks: BTreeMap<usize, Vec<&'a Definition>>,
+    renamed: &'a Definition,
+    name: &'a str,
+}
+
+impl<'a> Scopes<'a> {
+    fn new(analysis: &'a AnalysisOutput, renamed: &'a Definition, name: &'a str) -> Self {
+        let mut blocks: BTreeMap<usize, Vec<&Definition>> = BTreeMap::new();
+
+        for definition in analysis.references.definitions() {
+            blocks
+                .entry(definition.block)
+                .or_default()
+                .push(definition);
+        }
+
+        Self {
+            analysis,
+            blocks,
+            renamed,
+            name,
+        }
+    }
+
+    fn name_of(&self, definition: &'a Definition) -> &'a str {
+        if definition.span() == self.renamed.span() {
+            self.name
+        } else {
+            &definition.name.data
+        }
+    }
+
+    // Labels are bound after args, so they shadow any arg of the same name in the same block
+    fn resolve(&self, name: &str, mut block: usize) -> Option<&'a Definition> {
+        loop {
+            let found = self
+                .blocks
+                .get(&block)
+                .into_iter()
+                .flatten()
+                .copied()
+                .filter(|definition| self.name_of(definition) == name)
+                .max_by_key(|definition| definition.kind == DefinitionKind::Label);
+
+            if found.is_some() {
+                return found;
+            }
+
+            block = self.analysis.ir[block].source.parent?;
+        }
+    }
+
+    fn check_duplicates(&self) -> Result<(), RenameError> {
+        let duplicate = self
+            .blocks
+            .get(&self.renamed.block)
+            .into_iter()
+            .flatten()
+            .find(|definition| definition.span() != self.renamed.span() && definition.kind == self.renamed.kind && definition.name.data == self.name);
+
+        match duplicate {
+            Some(existing) => Err(RenameError::DuplicateSymbol {
+                existing: existing.span(),
+                renamed: self.renamed.span(),
+            }),
+            None => Ok(()),
+        }
+    }
+
+    // Only references to the renamed symbol, or to symbols already using the new name, can resolve differently
+    fn check_captures(&self) -> Result<(), RenameError> {
+        for definition in self.analysis.references.definitions() {
+            let name = self.name_of(definition);
+
+            if name != self.name {
+                continue;
+            }
+
+            for reference in &definition.uses {
+                match self.resolve(name, reference.block) {
+                    Some(resolved) if resolved.span() == definition.span() => (),
+                    Some(resolved) => {
+                        return Err(RenameError::CapturedReference {
+                            reference: reference.span,
+                            definition: resolved.span(),
+                        })
+                    }
+                    None => unreachable!("Existing reference must resolve to a definition"),
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Compute the edits needed to rename the symbol defined or used at `location` to `name`,
+/// which is assumed to already be a valid identifier
+pub fn rename(analysis: &AnalysisOutput, location: Location, name: &str) -> Result<Vec<Edit>, RenameError> {
+    if !analysis.errors.is_empty() {
+        return Err(RenameError::CompileErrors(analysis.errors.clone().into()));
+    }
+
+    let renamed = analysis
+        .references
+        .definition_at(location)
+        .ok_or(RenameError::NoSymbol(location))?;
+
+    let scopes = Scopes::new(analysis, renamed, name);
+    scopes.check_duplicates()?;
+    scopes.check_captures()?;
+
+    let mut edits: Vec<_> = std::iter::once(renamed.span())
+        .chain(
+            renamed
+                .uses
+                .iter()
+                .map(|reference| reference.span),
+        )
+        .map(|span| Edit { span, text: name.to_owned() })
+        .collect();
+
+    edits.sort();
+    Ok(edits)
+}
+
+/// Apply a set of non-overlapping edits to the source they were computed from
+#[must_use]
+pub fn apply_edits(source: &str, edits: &[Edit]) -> String {
+    let mut lines: Vec<Vec<char>> = source
+        .split('\n')
+        .map(|line| line.chars().collect())
+        .collect();
+
+    let mut edits = edits.to_vec();
+    edits.sort();
+
+    for edit in edits.iter().rev() {
+        let start = edit.span.start;
+        let end = edit.span.end;
+
+        let mut replaced = lines[start.line][..start.col].to_vec();
+        replaced.extend(edit.text.chars());
+        replaced.extend_from_slice(&lines[end.line][end.col..]);
+
+        lines.splice(start.line..=end.line, [replaced]);
+    }
+
+    lines
+        .into_iter()
+        .map(String::from_iter)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
@@ -0,0 +1,118 @@
+#![cfg(test)]
+
+use catastrophic_core::span::{Location, Span};
+use catastrophic_parser::parser::Parser;
+
+use crate::analyser::{Analyser, AnalysisOutput};
+
+use super::*;
+
+fn span(from_line: usize, from_col: usize, to_line: usize, to_col: usize) -> Span<()> {
+    Span::new(Location::new(from_line, from_col), Location::new(to_line, to_col), ())
+}
+
+fn analyse(input: &str) -> AnalysisOutput {
+    let ast = Parser::with_str(input)
+        .parse()
+        .unwrap()
+        .ast;
+
+    Analyser::analyse_ast_permissive(ast)
+}
+
+fn rename_test(input: &str, line: usize, col: usize, name: &str, expected: &str) {
+    let analysis = analyse(input);
+    let edits = rename(&analysis, Location::new(line, col), name).unwrap();
+
+    assert_eq!(apply_edits(input, &edits), expected);
+}
+
+fn rename_error(input: &str, line: usize, col: usize, name: &str) -> RenameError {
+    let analysis = analyse(input);
+    rename(&analysis, Location::new(line, col), name).unwrap_err()
+}
+
+#[test]
+fn rename_label_from_definition() {
+    rename_test("a: 5\na a + () .", 0, 0, "value", "value: 5\nvalue value + () .");
+}
+
+#[test]
+fn rename_label_from_use() {
+    rename_test("a: 5\na a + () .", 1, 2, "b", "b: 5\nb b + () .");
+}
+
+#[test]
+fn rename_arg() {
+    rename_test("x -> { x x * () }", 0, 7, "🐉", "🐉 -> { 🐉 🐉 * () }");
+}
+
+#[test]
+fn rename_leaves_shadowed_symbol() {
+    rename_test("x: 1\nx -> { x }\nx .", 0, 0, "y", "y: 1\nx -> { x }\ny .");
+}
+
+#[test]
+fn rename_edits_are_exact() {
+    let analysis = analyse("fn: { 1 }\nfn ()");
+    let edits = rename(&analysis, Location::new(0, 0), "f").unwrap();
+
+    assert_eq!(
+        edits,
+        [
+            Edit {
+                span: span(0, 0, 0, 2),
+                text: "f".to_owned()
+            },
+            Edit {
+                span: span(1, 0, 1, 2),
+                text: "f".to_owned()
+            },
+        ]
+    );
+}
+
+#[test]
+fn rename_without_symbol_fails() {
+    assert_eq!(rename_error("a: 5\n1 .", 1, 0, "b"), RenameError::NoSymbol(Location::new(1, 0)));
+}
+
+#[test]
+fn rename_to_duplicate_label_fails() {
+    assert_eq!(
+        rename_error("a: 5\nb: 6", 0, 0, "b"),
+        RenameError::DuplicateSymbol {
+            existing: span(1, 0, 1, 1),
+            renamed: span(0, 0, 0, 1)
+        }
+    );
+}
+
+#[test]
+fn rename_capturing_outer_use_fails() {
+    // Renaming `x` to `y` would make the inner use of `y` refer to the arg
+    assert_eq!(
+        rename_error("y: 1\nx -> { y }", 1, 0, "y"),
+        RenameError::CapturedReference {
+            reference: span(1, 7, 1, 8),
+            definition: span(1, 0, 1, 1)
+        }
+    );
+}
+
+#[test]
+fn rename_captured_by_inner_definition_fails() {
+    // Renaming `x` to `y` would make its use inside the block refer to the inner label
+    assert_eq!(
+        rename_error("x: 1\n{ y: 2\nx }", 0, 0, "y"),
+        RenameError::CapturedReference {
+            reference: span(2, 0, 2, 1),
+            definition: span(1, 2, 1, 3)
+        }
+    );
+}
+
+#[test]
+fn rename_with_compile_errors_fails() {
+    assert!(matches!(rename_error("a: 5\nb", 0, 0, "c"), RenameError::CompileErrors(_)));
+}
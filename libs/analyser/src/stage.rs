@@ -1,11 +1,26 @@
 use catastrophic_ast::ast;
-use catastrophic_core::{profiling::TimeScope, stage::Stage};
+use catastrophic_core::{profiling::TimeScope, span::Location, stage::Stage};
 use catastrophic_hir::hir;
 
-use crate::analyser::{Analyser, CompileErrors};
+use crate::{
+    analyser::{Analyser, CompileErrors},
+    rename::{self, Edit, RenameError},
+};
 
 pub struct AnalysisStage;
 
+pub struct RenameStage {
+    location: Location,
+    name: String,
+}
+
+impl RenameStage {
+    #[must_use]
+    pub fn new(location: Location, name: String) -> Self {
+        Self { location, name }
+    }
+}
+
 impl Stage<ast::Block> for AnalysisStage {
     type Output = Vec<hir::Block>;
     type Error = CompileErrors;
@@ -22,3 +37,21 @@ impl Stage<ast::Block> for AnalysisStage {
         "Unable to compile input"
     }
 }
+
+impl Stage<ast::Block> for RenameStage {
+    type Output = Vec<Edit>;
+    type Error = RenameError;
+
+    fn run(self, input: ast::Block, _: &mut TimeScope) -> Result<Self::Output, Self::Error> {
+        let analysis = Analyser::analyse_ast_permissive(input);
+        rename::rename(&analysis, self.location, &self.name)
+    }
+
+    fn name() -> &'static str {
+        "Rename"
+    }
+
+    fn error_context() -> &'static str {
+        "Unable to rename symbol"
+    }
+}
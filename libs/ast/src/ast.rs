@@ -1,9 +1,7 @@
-use std::{
-    collections::{hash_map::Entry, HashMap},
-    fmt::Display,
-};
+use std::fmt::Display;
 
 use catastrophic_core::{defines::ValueType, span::Span};
+use indexmap::{map::Entry, IndexMap};
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq, PartialOrd, Ord)]
 pub enum Builtin {
@@ -57,7 +55,7 @@ pub enum Instruction {
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Block {
     pub args: Vec<Span<String>>,
-    pub symbols: HashMap<String, Symbol>,
+    pub symbols: IndexMap<String, Symbol>,
     pub instrs: Vec<Span<Instruction>>,
     pub comments: Vec<Span<String>>,
 }
@@ -74,7 +72,7 @@ impl Block {
     pub fn no_args() -> Self {
         Self {
             args: Vec::new(),
-            symbols: HashMap::new(),
+            symbols: IndexMap::new(),
             instrs: Vec::new(),
             comments: Vec::new(),
         }
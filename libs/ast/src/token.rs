@@ -5,6 +5,8 @@ pub enum Token {
     Ident(String),
     String(String),
     Integer(ValueType),
+    /// An integer literal too large to fit in a value
+    IntegerTooLarge,
 
     Arrow,
     Parens,
@@ -1,5 +1,5 @@
 use std::{
-    collections::{btree_map::Entry, BTreeMap},
+    collections::{btree_map::Entry, BTreeMap, BTreeSet},
     path::PathBuf,
 };
 
@@ -35,6 +35,13 @@ pub struct State {
 
     getchar_fn: llvm::Function<fn() -> i32>,
 
+    abort_fn: llvm::Function<fn()>,
+
+    checked_plus_fn: llvm::Function<fn(i64, i64) -> i64>,
+    checked_minus_fn: llvm::Function<fn(i64, i64) -> i64>,
+    checked_multiply_fn: llvm::Function<fn(i64, i64) -> i64>,
+    checked_divide_fn: llvm::Function<fn(i64, i64) -> i64>,
+
     pop_fn: llvm::Function<fn() -> i64>,
     push_fn: llvm::Function<fn(i64)>,
     call_fn: llvm::Function<fn(i64) -> FunctionMetadata>,
@@ -42,6 +49,9 @@ pub struct State {
     closure_push_fn: llvm::Function<fn(i64)>,
     closure_offset_fn: llvm::Function<fn(i64, i64)>,
 
+    tail_call_fn: llvm::Function<fn()>,
+    tail_call_pointer: llvm::Value<*mut i64>,
+
     closure_stack: llvm::Value<*mut [i64; 2048]>,
     closure_stack_index: llvm::Value<*mut u32>,
 
@@ -49,6 +59,7 @@ pub struct State {
     index: llvm::Value<*mut u32>,
 
     functions: BTreeMap<FunctionKey, FunctionInfo>,
+    tail_called: BTreeSet<FunctionKey>,
 }
 
 impl FunctionKey {
@@ -105,16 +116,23 @@ impl State {
         let printf_str = module.add_named_string("format_number", "%lld");
         let printf_fn = module.add_function("printf");
         let getchar_fn = module.add_function("getchar");
+        let abort_fn = module.add_function("abort");
+        let checked_plus_fn = module.add_function("checked_plus");
+        let checked_minus_fn = module.add_function("checked_minus");
+        let checked_multiply_fn = module.add_function("checked_multiply");
+        let checked_divide_fn = module.add_function("checked_divide");
         let pop_fn = module.add_function("stack_pop");
         let push_fn = module.add_function("stack_push");
         let call_fn = module.add_function("call_index");
         let closure_push_fn = module.add_function("closure_push");
         let closure_offset_fn = module.add_function("closure_offset");
+        let tail_call_fn = module.add_function("tail_call");
 
         let closure_stack = module.add_named_array("closure_stack");
         let closure_stack_index = module.add_named_global("closure_stack_pointer", 0);
         let stack = module.add_named_array("value_stack");
         let index = module.add_named_global("value_stack_pointer", 0);
+        let tail_call_pointer = module.add_named_global("tail_call_pointer", 0);
 
         Self {
             ir,
@@ -124,16 +142,24 @@ impl State {
             printf_str,
             printf_fn,
             getchar_fn,
+            abort_fn,
+            checked_plus_fn,
+            checked_minus_fn,
+            checked_multiply_fn,
+            checked_divide_fn,
             pop_fn,
             push_fn,
             call_fn,
             closure_push_fn,
             closure_offset_fn,
+            tail_call_fn,
+            tail_call_pointer,
             closure_stack,
             closure_stack_index,
             stack,
             index,
             functions: BTreeMap::new(),
+            tail_called: BTreeSet::new(),
         }
     }
 
@@ -207,6 +233,36 @@ impl State {
             .build_void_ret();
     }
 
+    fn compile_tail_call(&mut self) {
+        let entry = self.tail_call_fn.add_block("entry");
+        let check = self.tail_call_fn.add_block("check");
+        let call = self.tail_call_fn.add_block("call");
+        let fin = self.tail_call_fn.add_block("fin");
+
+        entry.build().build_jump(&check);
+        fin.build().build_void_ret();
+
+        // A pointer of zero means there is no tail call left to make
+        let (pointer, builder) = check
+            .build()
+            .build_load(&self.tail_call_pointer);
+        builder.build_conditional_jump(&pointer, &fin, &call);
+
+        // Clear the pointer first, so the function we call can leave a tail call of its own
+        let (index, builder) = call
+            .build()
+            .build_store(&self.tail_call_pointer, &llvm::Value::constant(0i64))
+            .build_sub(&pointer, &llvm::Value::constant(1));
+
+        // Its captured args were pushed by the tail call, so it can be called directly
+        let ((f, _), builder) = builder.build_call(&self.call_fn, (index,));
+
+        builder
+            .build_call(&f, ())
+            .1
+            .build_jump(&check);
+    }
+
     fn compile_pop(&mut self) {
         let entry = self.pop_fn.add_block("entry");
         let zero = self.pop_fn.add_block("zero");
@@ -227,6 +283,113 @@ impl State {
             .build_ret(&value);
     }
 
+    /// Stop the program, as the result of some arithmetic can't be worked out
+    fn build_trap(&self, builder: llvm::Builder) {
+        builder
+            .build_call(&self.abort_fn, ())
+            .1
+            .build_unreachable();
+    }
+
+    /// Add or subtract, which has overflowed exactly when the result moved the wrong way from `x` for the sign of `y`
+    fn compile_checked_sum(&self, function: &llvm::Function<fn(i64, i64) -> i64>, subtract: bool) {
+        let (x, y) = function.params();
+
+        let entry = function.add_block("entry");
+        let done = function.add_block("done");
+        let overflow = function.add_block("overflow");
+
+        let builder = entry.build();
+        let (result, builder) = if subtract {
+            builder.build_sub(&x, &y)
+        } else {
+            builder.build_add(&x, &y)
+        };
+        let (negative, builder) = builder.build_lt(&y, &llvm::Value::constant(0i64));
+        let (moved, builder) = if subtract {
+            builder.build_gt(&result, &x)
+        } else {
+            builder.build_lt(&result, &x)
+        };
+        let (valid, builder) = builder.build_eq(&negative, &moved);
+        builder.build_conditional_jump(&valid, &overflow, &done);
+
+        done.build().build_ret(&result);
+        self.build_trap(overflow.build());
+    }
+
+    /// Multiply, which has overflowed when dividing the result by `x` doesn't give back `y`
+    fn compile_checked_multiply(&self) {
+        let function = &self.checked_multiply_fn;
+        let (x, y) = function.params();
+
+        let entry = function.add_block("entry");
+        let nonzero = function.add_block("nonzero");
+        let negate = function.add_block("negate");
+        let other = function.add_block("other");
+        let done = function.add_block("done");
+        let overflow = function.add_block("overflow");
+
+        let (result, builder) = entry.build().build_mul(&x, &y);
+        builder.build_conditional_jump(&x, &done, &nonzero);
+
+        // Dividing by -1 could overflow itself, but multiplying by it only overflows for the smallest value
+        let (is_negate, builder) = nonzero
+            .build()
+            .build_eq(&x, &llvm::Value::constant(-1i64));
+        builder.build_conditional_jump(&is_negate, &other, &negate);
+
+        let (is_min, builder) = negate
+            .build()
+            .build_eq(&y, &llvm::Value::constant(i64::MIN));
+        builder.build_conditional_jump(&is_min, &done, &overflow);
+
+        let (quotient, builder) = other.build().build_sdiv(&result, &x);
+        let (valid, builder) = builder.build_eq(&quotient, &y);
+        builder.build_conditional_jump(&valid, &overflow, &done);
+
+        done.build().build_ret(&result);
+        self.build_trap(overflow.build());
+    }
+
+    /// Divide, which fails when dividing by zero or when dividing the smallest value by -1 overflows
+    fn compile_checked_divide(&self) {
+        let function = &self.checked_divide_fn;
+        let (x, y) = function.params();
+
+        let entry = function.add_block("entry");
+        let nonzero = function.add_block("nonzero");
+        let negate = function.add_block("negate");
+        let divide = function.add_block("divide");
+        let fail = function.add_block("fail");
+
+        entry
+            .build()
+            .build_conditional_jump(&y, &fail, &nonzero);
+
+        let (is_negate, builder) = nonzero
+            .build()
+            .build_eq(&y, &llvm::Value::constant(-1i64));
+        builder.build_conditional_jump(&is_negate, &divide, &negate);
+
+        let (is_min, builder) = negate
+            .build()
+            .build_eq(&x, &llvm::Value::constant(i64::MIN));
+        builder.build_conditional_jump(&is_min, &divide, &fail);
+
+        let (result, builder) = divide.build().build_sdiv(&x, &y);
+        builder.build_ret(&result);
+
+        self.build_trap(fail.build());
+    }
+
+    fn compile_checked_arithmetic(&self) {
+        self.compile_checked_sum(&self.checked_plus_fn, false);
+        self.compile_checked_sum(&self.checked_minus_fn, true);
+        self.compile_checked_multiply();
+        self.compile_checked_divide();
+    }
+
     fn compile_bin_op(&mut self, bin_op: BinOp) {
         let entry = self.functions[&FunctionKey::BinOp(bin_op)]
             .value
@@ -236,7 +399,7 @@ impl State {
         let (x, builder) = builder.build_call(&self.pop_fn, ());
         let (y, builder) = builder.build_call(&self.pop_fn, ());
 
-        let (result, builder) = build_bin_op(builder, bin_op, x, y);
+        let (result, builder) = self.build_bin_op(builder, bin_op, x, y);
 
         builder
             .build_call(&self.push_fn, (result,))
@@ -263,6 +426,20 @@ impl State {
             .build_void_ret();
     }
 
+    /// Arithmetic which can fail calls a function which stops the program if it does, like the interpreters do
+    fn build_bin_op(&self, builder: llvm::Builder, bin_op: BinOp, x: llvm::Value<i64>, y: llvm::Value<i64>) -> (llvm::Value<i64>, llvm::Builder) {
+        match bin_op {
+            BinOp::Plus => builder.build_call(&self.checked_plus_fn, (x, y)),
+            BinOp::Minus => builder.build_call(&self.checked_minus_fn, (x, y)),
+            BinOp::Multiply => builder.build_call(&self.checked_multiply_fn, (x, y)),
+            BinOp::Divide => builder.build_call(&self.checked_divide_fn, (x, y)),
+            BinOp::Equals => builder.build_eq(&x, &y),
+            BinOp::GreaterThan => builder.build_gt(&x, &y),
+            BinOp::LessThan => builder.build_lt(&x, &y),
+            BinOp::Random => unimplemented!(),
+        }
+    }
+
     fn build_value(&mut self, builder: llvm::Builder, args: &[llvm::Value<i64>], value: &Value) -> (llvm::Value<i64>, llvm::Builder) {
         match value {
             Value::Arg(arg) => (args[*arg], builder),
@@ -278,7 +455,7 @@ impl State {
                 let (x, builder) = self.build_value(builder, args, x);
                 let (y, builder) = self.build_value(builder, args, y);
 
-                build_bin_op(builder, *bin_op, x, y)
+                self.build_bin_op(builder, *bin_op, x, y)
             }
             Value::ImmediateTriOp(tri_op, ref x, ref y, ref z) => {
                 let (x, builder) = self.build_value(builder, args, x);
@@ -309,7 +486,12 @@ impl State {
             .1;
 
         // Call the function!
-        builder.build_call(&f, ()).1
+        let builder = builder.build_call(&f, ()).1;
+
+        // Finish any tail calls it left behind
+        builder
+            .build_call(&self.tail_call_fn, ())
+            .1
     }
 
     fn build_command_instr(&self, builder: llvm::Builder, command: Command) -> llvm::Builder {
@@ -382,7 +564,7 @@ impl State {
                 let (x, builder) = self.build_value(builder, args, x);
                 let (y, builder) = self.build_value(builder, args, y);
 
-                let (result, builder) = build_bin_op(builder, *bin_op, x, y);
+                let (result, builder) = self.build_bin_op(builder, *bin_op, x, y);
 
                 builder
                     .build_call(&self.push_fn, (result,))
@@ -402,8 +584,30 @@ impl State {
         }
     }
 
-    fn build_immediate_call_instr(&mut self, mut builder: llvm::Builder, args: &[llvm::Value<i64>], function: &Function) -> llvm::Builder {
-        let info = self.queue_function(FunctionKey::from_function(function));
+    fn build_function_call(&self, mut builder: llvm::Builder, args: &[llvm::Value<i64>], function: &Function, info: FunctionInfo) -> llvm::Builder {
+        for arg in args.iter().take(info.offset).rev() {
+            builder = builder
+                .build_call(&self.push_fn, (*arg,))
+                .1;
+        }
+
+        let builder = builder.build_call(&info.value, ()).1;
+
+        // Only blocks can leave tail calls behind
+        match function {
+            Function::Block(_) => {
+                builder
+                    .build_call(&self.tail_call_fn, ())
+                    .1
+            }
+            Function::BinOp(_) | Function::TriOp(_) => builder,
+        }
+    }
+
+    fn build_tail_call(&mut self, mut builder: llvm::Builder, args: &[llvm::Value<i64>], function: &Function) -> llvm::Builder {
+        let key = FunctionKey::from_function(function);
+        let info = self.queue_function(key);
+        self.tail_called.insert(key);
 
         for arg in args.iter().take(info.offset).rev() {
             builder = builder
@@ -411,7 +615,14 @@ impl State {
                 .1;
         }
 
-        builder.build_call(&info.value, ()).1
+        // Rather than calling the function, leave it to be called by the trampoline once this block has returned
+        builder.build_store(&self.tail_call_pointer, &llvm::Value::constant(info.index as i64 + 1))
+    }
+
+    fn build_immediate_call_instr(&mut self, builder: llvm::Builder, args: &[llvm::Value<i64>], function: &Function) -> llvm::Builder {
+        let info = self.queue_function(FunctionKey::from_function(function));
+
+        self.build_function_call(builder, args, function, info)
     }
 
     fn build_immediate_conditional_call_instr(
@@ -420,44 +631,25 @@ impl State {
         args: &[llvm::Value<i64>],
         function_info: FunctionInfo,
         value: &Value,
-        x: &Function,
-        y: &Function,
+        (x, y): (&Function, &Function),
+        tail: bool,
     ) -> llvm::Builder {
-        let x = self.queue_function(FunctionKey::from_function(x));
-        let y = self.queue_function(FunctionKey::from_function(y));
-
         let cont = function_info.value.add_block("cont");
 
         let x_block = function_info.value.add_block("x");
-
-        let mut builder = x_block.build();
-
-        for arg in args.iter().take(x.offset).rev() {
-            builder = builder
-                .build_call(&self.push_fn, (*arg,))
-                .1;
-        }
-
-        builder
-            .build_call(&x.value, ())
-            .1
-            .build_jump(&cont);
-
         let y_block = function_info.value.add_block("y");
 
-        let mut builder = y_block.build();
+        for (block, function) in [(&x_block, x), (&y_block, y)] {
+            let builder = if tail {
+                self.build_tail_call(block.build(), args, function)
+            } else {
+                let info = self.queue_function(FunctionKey::from_function(function));
+                self.build_function_call(block.build(), args, function, info)
+            };
 
-        for arg in args.iter().take(y.offset).rev() {
-            builder = builder
-                .build_call(&self.push_fn, (*arg,))
-                .1;
+            builder.build_jump(&cont);
         }
 
-        builder
-            .build_call(&y.value, ())
-            .1
-            .build_jump(&cont);
-
         let (value, builder) = self.build_value(block_builder, args, value);
         builder.build_conditional_jump(&value, &y_block, &x_block);
 
@@ -487,7 +679,11 @@ impl State {
                 Instr::Push(ref value) => self.build_push_instr(block_builder, &args, value),
                 Instr::ImmediateCall(function) => self.build_immediate_call_instr(block_builder, &args, function),
                 Instr::ImmediateConditionalCall(value, x, y) => {
-                    self.build_immediate_conditional_call_instr(block_builder, &args, function_info, value, x, y)
+                    self.build_immediate_conditional_call_instr(block_builder, &args, function_info, value, (x, y), false)
+                }
+                Instr::TailCall(function) => self.build_tail_call(block_builder, &args, function),
+                Instr::ConditionalTailCall(value, x, y) => {
+                    self.build_immediate_conditional_call_instr(block_builder, &args, function_info, value, (x, y), true)
                 }
             }
         }
@@ -533,10 +729,15 @@ impl State {
         let entry = self.call_fn.add_block("entry");
         let builder = entry.build();
 
+        // Blocks which are only ever called directly can never be looked up by index, unless the trampoline needs them for a tail call
         let blocks: Vec<_> = self
             .functions
-            .values()
-            .map(|f| {
+            .iter()
+            .filter(|(function, _)| match function {
+                FunctionKey::Block(index) => self.ir[*index].first_class || self.tail_called.contains(function),
+                FunctionKey::BinOp(_) | FunctionKey::TriOp(_) => true,
+            })
+            .map(|(_, f)| {
                 let block = self
                     .call_fn
                     .add_block(format!("block_{}", f.index));
@@ -568,6 +769,8 @@ impl State {
             .build()
             .build_call(&self.functions[&FunctionKey::Block(0)].value, ())
             .1
+            .build_call(&self.tail_call_fn, ())
+            .1
             .build_call(&self.pop_fn, ());
         builder.build_ret(&result);
     }
@@ -577,6 +780,8 @@ impl State {
         self.compile_push();
         self.compile_closure_push();
         self.compile_closure_offset();
+        self.compile_tail_call();
+        self.compile_checked_arithmetic();
         self.queue_function(FunctionKey::Block(0));
 
         while let Some(function) = self.queue.pop() {
@@ -601,16 +806,3 @@ fn build_tri_op(
         TriOp::IfThenElse => builder.build_conditional_value(&x, &y, &z),
     }
 }
-
-fn build_bin_op(builder: llvm::Builder, bin_op: BinOp, x: llvm::Value<i64>, y: llvm::Value<i64>) -> (llvm::Value<i64>, llvm::Builder) {
-    match bin_op {
-        BinOp::Plus => builder.build_add(&x, &y),
-        BinOp::Minus => builder.build_sub(&x, &y),
-        BinOp::Multiply => builder.build_mul(&x, &y),
-        BinOp::Divide => builder.build_sdiv(&x, &y),
-        BinOp::Equals => builder.build_eq(&x, &y),
-        BinOp::GreaterThan => builder.build_gt(&x, &y),
-        BinOp::LessThan => builder.build_lt(&x, &y),
-        BinOp::Random => unimplemented!(),
-    }
-}
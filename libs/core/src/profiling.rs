@@ -4,6 +4,7 @@ struct TimeEntry {
     label: String,
     start: Instant,
     end: Option<Instant>,
+    note: Option<String>,
 }
 
 pub struct TimeKeeper {
@@ -23,6 +24,7 @@ impl TimeKeeper {
             label: label.to_string(),
             start: Instant::now(),
             end: None,
+            note: None,
         };
 
         Self { entries: vec![entry] }
@@ -36,11 +38,16 @@ impl TimeKeeper {
         TimeScope::finish(&mut self.entries, 0);
 
         for entry in self.entries {
-            println!(
+            print!(
                 "{}: \t\t{:0<11}s",
                 entry.label,
                 (entry.end.unwrap_or_else(Instant::now) - entry.start).as_secs_f64()
             );
+
+            match entry.note {
+                Some(note) => println!("\t{note}"),
+                None => println!(),
+            }
         }
     }
 }
@@ -50,6 +57,11 @@ impl TimeScope<'_> {
         Self::make(self.entries, self.indent + 1, label.to_string())
     }
 
+    /// Attach a note to this scope, shown alongside its time
+    pub fn note<S: ToString>(&mut self, note: &S) {
+        self.entries[self.start].note = Some(note.to_string());
+    }
+
     fn make(entries: &mut Vec<TimeEntry>, indent: usize, mut label: String) -> TimeScope<'_> {
         for _ in 0..indent {
             label.insert(0, ' ');
@@ -59,6 +71,7 @@ impl TimeScope<'_> {
             label,
             start: Instant::now(),
             end: None,
+            note: None,
         };
 
         let start = entries.len();
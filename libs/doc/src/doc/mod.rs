@@ -0,0 +1,135 @@
+use std::collections::BTreeMap;
+
+use catastrophic_ast::ast::{self, InstrValue, Instruction, SymbolValue};
+use catastrophic_core::span::{Location, Span};
+
+mod test;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SymbolDoc {
+    pub name: String,
+    pub args: Vec<String>,
+    pub value: String,
+    pub comment: Option<String>,
+    pub location: Location,
+    pub symbols: Vec<SymbolDoc>,
+}
+
+impl SymbolDoc {
+    /// The symbol as it would be written in source, with the body of a block elided
+    #[must_use]
+    pub fn signature(&self) -> String {
+        let args = self
+            .args
+            .iter()
+            .flat_map(|arg| [arg.as_str(), " -> "])
+            .collect::<String>();
+
+        format!("{}: {args}{}", self.name, self.value)
+    }
+}
+
+// Whether any item of the block shares the comment's line and comes before it
+fn is_trailing(block: &ast::Block, comment: &Span<String>) -> bool {
+    let before = |span: Span<()>| span.start < comment.start && span.end.line >= comment.start.line;
+
+    block
+        .args
+        .iter()
+        .map(|arg| arg.swap(()))
+        .chain(
+            block
+                .symbols
+                .values()
+                .flat_map(|symbol| [symbol.name_span, symbol.value.swap(())]),
+        )
+        .chain(
+            block
+                .instrs
+                .iter()
+                .map(|instr| instr.swap(())),
+        )
+        .any(before)
+}
+
+/// Find the comment attached to a symbol, made up of the run of whole-line comments directly above its label
+#[must_use]
+pub fn symbol_comment(block: &ast::Block, symbol: &ast::Symbol) -> Option<String> {
+    let comments = block
+        .comments
+        .iter()
+        .filter(|comment| !is_trailing(block, comment))
+        .map(|comment| (comment.start.line, comment.data.as_str()))
+        .collect::<BTreeMap<_, _>>();
+
+    let mut lines = Vec::new();
+    let mut line = symbol.name_span.start.line;
+
+    while let Some(comment) = line
+        .checked_sub(1)
+        .and_then(|above| comments.get(&above))
+    {
+        lines.push(comment.trim_end_matches(['\n', '\r']));
+        line -= 1;
+    }
+
+    if lines.is_empty() {
+        return None;
+    }
+
+    lines.reverse();
+
+    // Comments are usually written with a space after the `#`, which isn't part of the text
+    let text = lines
+        .iter()
+        .map(|line| line.strip_prefix(' ').unwrap_or(line))
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    Some(text)
+}
+
+/// Collect documentation for every symbol defined within a block, including those nested in its blocks
+#[must_use]
+pub fn document(block: &ast::Block) -> Vec<SymbolDoc> {
+    let mut symbols = block
+        .symbols
+        .iter()
+        .map(|(name, symbol)| {
+            let (args, value, symbols) = match &symbol.value.data {
+                SymbolValue::Number(value) => (Vec::new(), value.to_string(), Vec::new()),
+                SymbolValue::Builtin(builtin) => (Vec::new(), builtin.to_string(), Vec::new()),
+                SymbolValue::Block(inner) => {
+                    let mut args = inner.args.clone();
+                    args.sort_by_key(|arg| arg.start);
+
+                    let args = args
+                        .into_iter()
+                        .map(|arg| arg.data)
+                        .collect();
+
+                    (args, "{ ... }".to_owned(), document(inner))
+                }
+            };
+
+            SymbolDoc {
+                name: name.clone(),
+                args,
+                value,
+                comment: symbol_comment(block, symbol),
+                location: symbol.name_span.start,
+                symbols,
+            }
+        })
+        .collect::<Vec<_>>();
+
+    // Symbols within anonymous blocks have no name of their own to sit under, so belong to the enclosing block
+    for instr in &block.instrs {
+        if let Instruction::Push(InstrValue::Block(inner)) = &instr.data {
+            symbols.extend(document(inner));
+        }
+    }
+
+    symbols.sort_by_key(|symbol| symbol.location);
+    symbols
+}
@@ -0,0 +1,70 @@
+#![cfg(test)]
+
+use catastrophic_parser::parser::Parser;
+
+use super::*;
+
+fn document_str(input: &str) -> Vec<SymbolDoc> {
+    let ast = Parser::with_str(input)
+        .parse()
+        .unwrap()
+        .ast;
+
+    document(&ast)
+}
+
+fn names(symbols: &[SymbolDoc]) -> Vec<&str> {
+    symbols
+        .iter()
+        .map(|symbol| symbol.name.as_str())
+        .collect()
+}
+
+#[test]
+fn document_symbol_values() {
+    let symbols = document_str("a: 5\nb: +\nc: x -> y -> { x y + () }");
+
+    assert_eq!(names(&symbols), ["a", "b", "c"]);
+    assert_eq!(symbols[0].signature(), "a: 5");
+    assert_eq!(symbols[1].signature(), "b: +");
+    assert_eq!(symbols[2].signature(), "c: x -> y -> { ... }");
+    assert_eq!(symbols[2].location, Location::new(2, 0));
+}
+
+#[test]
+fn document_comment_above_label() {
+    let symbols = document_str("# Adds one\n# to a number\nincrement: x -> { x 1 + () }");
+
+    assert_eq!(symbols[0].comment.as_deref(), Some("Adds one\nto a number"));
+}
+
+#[test]
+fn document_comment_separated_by_blank_line() {
+    let symbols = document_str("# Unrelated\n\na: 5");
+
+    assert_eq!(symbols[0].comment, None);
+}
+
+#[test]
+fn document_trailing_comment_not_attached() {
+    let symbols = document_str("a: 5 # Not about b\nb: 6");
+
+    assert_eq!(symbols[1].comment, None);
+}
+
+#[test]
+fn document_comment_only_attached_to_following_label() {
+    let symbols = document_str("# About a\na: 5\nb: 6");
+
+    assert_eq!(symbols[0].comment.as_deref(), Some("About a"));
+    assert_eq!(symbols[1].comment, None);
+}
+
+#[test]
+fn document_nested_symbols() {
+    let symbols = document_str("outer: x -> {\n    # Inner value\n    inner: 5\n}\n{\n    hidden: 6\n}");
+
+    assert_eq!(names(&symbols), ["outer", "hidden"]);
+    assert_eq!(names(&symbols[0].symbols), ["inner"]);
+    assert_eq!(symbols[0].symbols[0].comment.as_deref(), Some("Inner value"));
+}
@@ -0,0 +1,3 @@
+pub mod doc;
+pub mod render;
+pub mod stage;
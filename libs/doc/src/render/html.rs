@@ -0,0 +1,83 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::doc::SymbolDoc;
+
+/// Renders symbol documentation as a standalone HTML page, with nested symbols as nested lists
+pub struct Html<'a> {
+    source: &'a str,
+    symbols: &'a [SymbolDoc],
+}
+
+struct Escaped<'a>(&'a str);
+
+impl Display for Escaped<'_> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        for c in self.0.chars() {
+            match c {
+                '&' => fmt.write_str("&amp;")?,
+                '<' => fmt.write_str("&lt;")?,
+                '>' => fmt.write_str("&gt;")?,
+                '"' => fmt.write_str("&quot;")?,
+                '\'' => fmt.write_str("&#39;")?,
+                c => write!(fmt, "{c}")?,
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<'a> Html<'a> {
+    #[must_use]
+    pub fn new(source: &'a str, symbols: &'a [SymbolDoc]) -> Self {
+        Self { source, symbols }
+    }
+
+    fn write_symbols(&self, fmt: &mut Formatter, symbols: &[SymbolDoc]) -> fmt::Result {
+        if symbols.is_empty() {
+            return Ok(());
+        }
+
+        writeln!(fmt, "<ul>")?;
+
+        for symbol in symbols {
+            let line = symbol.location.line + 1;
+
+            writeln!(fmt, "<li>")?;
+            writeln!(
+                fmt,
+                "<code>{}</code> <a href=\"{}#L{line}\">{}:{line}</a>",
+                Escaped(&symbol.signature()),
+                Escaped(self.source),
+                Escaped(self.source)
+            )?;
+
+            if let Some(comment) = &symbol.comment {
+                for paragraph in comment.split("\n\n") {
+                    writeln!(fmt, "<p>{}</p>", Escaped(paragraph.trim()))?;
+                }
+            }
+
+            self.write_symbols(fmt, &symbol.symbols)?;
+            writeln!(fmt, "</li>")?;
+        }
+
+        writeln!(fmt, "</ul>")
+    }
+}
+
+impl Display for Html<'_> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        writeln!(fmt, "<!DOCTYPE html>")?;
+        writeln!(fmt, "<html>")?;
+        writeln!(fmt, "<head>")?;
+        writeln!(fmt, "<meta charset=\"utf-8\">")?;
+        writeln!(fmt, "<title>{}</title>", Escaped(self.source))?;
+        writeln!(fmt, "</head>")?;
+        writeln!(fmt, "<body>")?;
+        writeln!(fmt, "<h1><code>{}</code></h1>", Escaped(self.source))?;
+        self.write_symbols(fmt, self.symbols)?;
+        writeln!(fmt, "</body>")?;
+        writeln!(fmt, "</html>")
+    }
+}
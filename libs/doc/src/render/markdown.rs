@@ -0,0 +1,53 @@
+use std::fmt::{self, Display, Formatter};
+
+use crate::doc::SymbolDoc;
+
+/// Renders symbol documentation as a Markdown page, with nesting shown through heading levels
+pub struct Markdown<'a> {
+    source: &'a str,
+    symbols: &'a [SymbolDoc],
+}
+
+impl<'a> Markdown<'a> {
+    #[must_use]
+    pub fn new(source: &'a str, symbols: &'a [SymbolDoc]) -> Self {
+        Self { source, symbols }
+    }
+
+    fn write_symbol(&self, fmt: &mut Formatter, symbol: &SymbolDoc, depth: usize) -> fmt::Result {
+        let line = symbol.location.line + 1;
+
+        writeln!(fmt)?;
+        writeln!(fmt, "{} `{}`", "#".repeat((depth + 2).min(6)), symbol.name)?;
+        writeln!(fmt)?;
+        writeln!(fmt, "```")?;
+        writeln!(fmt, "{}", symbol.signature())?;
+        writeln!(fmt, "```")?;
+
+        if let Some(comment) = &symbol.comment {
+            writeln!(fmt)?;
+            writeln!(fmt, "{comment}")?;
+        }
+
+        writeln!(fmt)?;
+        writeln!(fmt, "[{}:{line}]({}#L{line})", self.source, self.source)?;
+
+        for nested in &symbol.symbols {
+            self.write_symbol(fmt, nested, depth + 1)?;
+        }
+
+        Ok(())
+    }
+}
+
+impl Display for Markdown<'_> {
+    fn fmt(&self, fmt: &mut Formatter) -> fmt::Result {
+        writeln!(fmt, "# `{}`", self.source)?;
+
+        for symbol in self.symbols {
+            self.write_symbol(fmt, symbol, 0)?;
+        }
+
+        Ok(())
+    }
+}
@@ -0,0 +1,5 @@
+pub use self::{html::Html, markdown::Markdown};
+
+mod html;
+mod markdown;
+mod test;
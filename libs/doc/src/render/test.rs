@@ -0,0 +1,44 @@
+#![cfg(test)]
+
+use catastrophic_core::span::Location;
+
+use super::*;
+use crate::doc::SymbolDoc;
+
+fn symbols() -> Vec<SymbolDoc> {
+    vec![SymbolDoc {
+        name: "less".into(),
+        args: vec!["a".into(), "b".into()],
+        value: "{ ... }".into(),
+        comment: Some("Whether a < b".into()),
+        location: Location::new(2, 0),
+        symbols: vec![SymbolDoc {
+            name: "zero".into(),
+            args: Vec::new(),
+            value: "0".into(),
+            comment: None,
+            location: Location::new(3, 4),
+            symbols: Vec::new(),
+        }],
+    }]
+}
+
+#[test]
+fn render_markdown() {
+    let symbols = symbols();
+
+    assert_eq!(
+        Markdown::new("lib.cat", &symbols).to_string(),
+        "# `lib.cat`\n\n## `less`\n\n```\nless: a -> b -> { ... }\n```\n\nWhether a < b\n\n[lib.cat:3](lib.cat#L3)\n\n### `zero`\n\n```\nzero: 0\n```\n\n[lib.cat:4](lib.cat#L4)\n"
+    );
+}
+
+#[test]
+fn render_html() {
+    let symbols = symbols();
+    let html = Html::new("lib.cat", &symbols).to_string();
+
+    assert!(html.contains("<code>less: a -&gt; b -&gt; { ... }</code> <a href=\"lib.cat#L3\">lib.cat:3</a>"));
+    assert!(html.contains("<p>Whether a &lt; b</p>"));
+    assert!(html.contains("<ul>\n<li>\n<code>zero: 0</code>"));
+}
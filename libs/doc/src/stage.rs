@@ -0,0 +1,36 @@
+use catastrophic_ast::ast;
+use catastrophic_core::{
+    error::{context::ErrorProvider, writer::ErrorWriter},
+    profiling::TimeScope,
+    stage::Stage,
+};
+
+use crate::doc::{self, SymbolDoc};
+
+pub struct DocStage;
+
+impl Stage<ast::Block> for DocStage {
+    type Output = Vec<SymbolDoc>;
+    type Error = NoError;
+
+    fn run(self, input: ast::Block, _: &mut TimeScope) -> Result<Self::Output, Self::Error> {
+        Ok(doc::document(&input))
+    }
+
+    fn name() -> &'static str {
+        "Documentation"
+    }
+
+    fn error_context() -> &'static str {
+        "Unable to document input"
+    }
+}
+
+#[derive(Debug)]
+pub enum NoError {}
+
+impl ErrorProvider for NoError {
+    fn write_errors(&self, _: &mut dyn ErrorWriter) -> std::fmt::Result {
+        Ok(())
+    }
+}
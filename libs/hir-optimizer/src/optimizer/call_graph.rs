@@ -0,0 +1,174 @@
+use std::collections::BTreeSet;
+
+use catastrophic_mir::mir;
+
+/// Which blocks each block refers to, whether by calling them immediately or by pushing them as values
+pub struct CallGraph {
+    references: Vec<BTreeSet<usize>>,
+    referrers: Vec<BTreeSet<usize>>,
+    first_class: BTreeSet<usize>,
+}
+
+pub(crate) fn value_blocks(value: &mir::Value, blocks: &mut BTreeSet<usize>) {
+    match value {
+        mir::Value::Function(function) => function_blocks(*function, blocks),
+        mir::Value::ImmediateBinOp(_, x, y) => {
+            value_blocks(x, blocks);
+            value_blocks(y, blocks);
+        }
+        mir::Value::ImmediateTriOp(_, x, y, z) => {
+            value_blocks(x, blocks);
+            value_blocks(y, blocks);
+            value_blocks(z, blocks);
+        }
+        mir::Value::Arg(_) | mir::Value::Number(_) => (),
+    }
+}
+
+fn function_blocks(function: mir::Function, blocks: &mut BTreeSet<usize>) {
+    if let mir::Function::Block(index) = function {
+        blocks.insert(index);
+    }
+}
+
+/// Every block referenced by the given block
+#[must_use]
+pub fn referenced_blocks(block: &mir::Block) -> BTreeSet<usize> {
+    let mut blocks = first_class_blocks(block);
+
+    for instr in &block.instrs {
+        match &instr.data {
+            mir::Instr::ImmediateCall(function) | mir::Instr::TailCall(function) => function_blocks(*function, &mut blocks),
+            mir::Instr::ImmediateConditionalCall(_, x, y) | mir::Instr::ConditionalTailCall(_, x, y) => {
+                function_blocks(*x, &mut blocks);
+                function_blocks(*y, &mut blocks);
+            }
+            mir::Instr::Push(_) | mir::Instr::Command(_) => (),
+        }
+    }
+
+    blocks
+}
+
+/// Every block the given block uses as a value, which could then be called from anywhere
+#[must_use]
+pub fn first_class_blocks(block: &mir::Block) -> BTreeSet<usize> {
+    let mut blocks = BTreeSet::new();
+
+    for instr in &block.instrs {
+        match &instr.data {
+            mir::Instr::Push(value) | mir::Instr::ImmediateConditionalCall(value, _, _) | mir::Instr::ConditionalTailCall(value, _, _) => {
+                value_blocks(value, &mut blocks);
+            }
+            mir::Instr::ImmediateCall(_) | mir::Instr::TailCall(_) | mir::Instr::Command(_) => (),
+        }
+    }
+
+    blocks
+}
+
+impl CallGraph {
+    #[must_use]
+    pub fn new(blocks: &[mir::Block]) -> Self {
+        let references = blocks
+            .iter()
+            .map(referenced_blocks)
+            .collect::<Vec<_>>();
+
+        let mut referrers = vec![BTreeSet::new(); blocks.len()];
+
+        for (index, references) in references.iter().enumerate() {
+            for &reference in references {
+                referrers[reference].insert(index);
+            }
+        }
+
+        let first_class = blocks
+            .iter()
+            .flat_map(first_class_blocks)
+            .collect();
+
+        Self {
+            references,
+            referrers,
+            first_class,
+        }
+    }
+
+    /// Add the references of a block added to the program after the graph was made, which may refer to blocks yet to be added
+    pub fn add_block(&mut self, block: &mir::Block) {
+        self.references.push(BTreeSet::new());
+        self.update_block(self.references.len() - 1, block);
+    }
+
+    /// Bring the references of a block up to date after its instructions have changed, rather than remaking the whole graph
+    ///
+    /// Blocks it no longer uses as values are still counted as first class, as other blocks may still use them
+    pub fn update_block(&mut self, index: usize, block: &mir::Block) {
+        for reference in std::mem::take(&mut self.references[index]) {
+            self.referrers[reference].remove(&index);
+        }
+
+        let references = referenced_blocks(block);
+
+        let len = references
+            .last()
+            .map_or(0, |last| last + 1)
+            .max(self.references.len());
+
+        if self.referrers.len() < len {
+            self.referrers
+                .resize(len, BTreeSet::new());
+        }
+
+        for &reference in &references {
+            self.referrers[reference].insert(index);
+        }
+
+        self.references[index] = references;
+        self.first_class
+            .extend(first_class_blocks(block));
+    }
+
+    /// The blocks the given block refers to directly
+    #[must_use]
+    pub fn references(&self, index: usize) -> &BTreeSet<usize> {
+        &self.references[index]
+    }
+
+    /// The blocks which refer directly to the given block
+    #[must_use]
+    pub fn referrers(&self, index: usize) -> &BTreeSet<usize> {
+        &self.referrers[index]
+    }
+
+    /// Every block which can be reached by following references from the given block, which only includes itself if it is recursive
+    #[must_use]
+    pub fn reachable(&self, index: usize) -> BTreeSet<usize> {
+        let mut seen = BTreeSet::new();
+        let mut queue = self
+            .references(index)
+            .iter()
+            .copied()
+            .collect::<Vec<_>>();
+
+        while let Some(next) = queue.pop() {
+            if seen.insert(next) {
+                queue.extend(self.references(next));
+            }
+        }
+
+        seen
+    }
+
+    /// Whether the given block is used as a value anywhere, rather than only ever being called directly
+    #[must_use]
+    pub fn is_first_class(&self, index: usize) -> bool {
+        self.first_class.contains(&index)
+    }
+
+    #[must_use]
+    pub fn is_recursive(&self, index: usize) -> bool {
+        self.reachable(index).contains(&index)
+    }
+}
@@ -1,20 +1,320 @@
+use std::collections::BTreeSet;
+
 use catastrophic_core::span::Span;
 use catastrophic_mir::mir;
 
+use super::{
+    call_graph::{self, CallGraph},
+    remark::Remarks,
+    statistics::Statistics,
+    Options, PassError,
+};
+
+/// The whole program being optimized, which passes are free to rewrite
 pub struct OptimizationContext<'a> {
-    current: &'a mir::Block,
+    blocks: Vec<mir::Block>,
+    options: &'a Options,
+    statistics: Statistics,
+    remarks: Remarks,
+    specialisations: Vec<Specialisation>,
+}
+
+/// A copy of a block made for a particular set of constant leading arguments
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Specialisation {
+    pub block: usize,
+    pub args: Vec<mir::Value>,
+    pub specialised: usize,
+}
+
+/// A view of a single block being rewritten, alongside the rest of the program
+pub struct BlockContext<'a> {
+    blocks: &'a [mir::Block],
+    current: usize,
+    options: &'a Options,
+    remarks: &'a mut Remarks,
+}
+
+fn renumber_function(function: mir::Function, indices: &[Option<usize>]) -> mir::Function {
+    match function {
+        mir::Function::Block(index) => mir::Function::Block(indices[index].expect("removed block is still referenced")),
+        function => function,
+    }
+}
+
+fn renumber_value(value: &mir::Value, indices: &[Option<usize>]) -> mir::Value {
+    match value {
+        mir::Value::Function(function) => mir::Value::Function(renumber_function(*function, indices)),
+        mir::Value::ImmediateBinOp(op, x, y) => {
+            mir::Value::ImmediateBinOp(*op, Box::new(renumber_value(x, indices)), Box::new(renumber_value(y, indices)))
+        }
+        mir::Value::ImmediateTriOp(op, x, y, z) => mir::Value::ImmediateTriOp(
+            *op,
+            Box::new(renumber_value(x, indices)),
+            Box::new(renumber_value(y, indices)),
+            Box::new(renumber_value(z, indices)),
+        ),
+        value => value.clone(),
+    }
+}
+
+fn renumber_instr(instr: &mir::Instr, indices: &[Option<usize>]) -> mir::Instr {
+    match instr {
+        mir::Instr::Push(value) => mir::Instr::Push(renumber_value(value, indices)),
+        mir::Instr::ImmediateCall(function) => mir::Instr::ImmediateCall(renumber_function(*function, indices)),
+        mir::Instr::ImmediateConditionalCall(value, x, y) => mir::Instr::ImmediateConditionalCall(
+            renumber_value(value, indices),
+            renumber_function(*x, indices),
+            renumber_function(*y, indices),
+        ),
+        mir::Instr::TailCall(function) => mir::Instr::TailCall(renumber_function(*function, indices)),
+        mir::Instr::ConditionalTailCall(value, x, y) => mir::Instr::ConditionalTailCall(
+            renumber_value(value, indices),
+            renumber_function(*x, indices),
+            renumber_function(*y, indices),
+        ),
+        mir::Instr::Command(command) => mir::Instr::Command(*command),
+    }
 }
 
 impl<'a> OptimizationContext<'a> {
-    pub fn new(current: &'a mir::Block) -> Self {
-        Self { current }
+    #[must_use]
+    pub fn new(blocks: Vec<mir::Block>, options: &'a Options) -> Self {
+        Self::with_remarks(blocks, options, Remarks::default())
+    }
+
+    #[must_use]
+    pub fn with_remarks(blocks: Vec<mir::Block>, options: &'a Options, remarks: Remarks) -> Self {
+        Self {
+            blocks,
+            options,
+            statistics: Statistics::default(),
+            remarks,
+            specialisations: Vec::new(),
+        }
+    }
+
+    #[must_use]
+    pub fn blocks(&self) -> &[mir::Block] {
+        &self.blocks
+    }
+
+    pub fn block_mut(&mut self, index: usize) -> &mut mir::Block {
+        &mut self.blocks[index]
+    }
+
+    #[must_use]
+    pub fn options(&self) -> &'a Options {
+        self.options
+    }
+
+    #[must_use]
+    pub fn call_graph(&self) -> CallGraph {
+        CallGraph::new(&self.blocks)
+    }
+
+    pub fn remarks(&mut self) -> &mut Remarks {
+        &mut self.remarks
+    }
+
+    /// Check that the program is well formed, blaming the given pass if it isn't
+    ///
+    /// # Errors
+    ///
+    /// Returns the first problem found with the program
+    pub fn verify(&self, pass: &'static str) -> Result<(), PassError> {
+        mir::verify(&self.blocks).map_err(|error| PassError { pass, error })
+    }
+
+    #[must_use]
+    pub fn into_blocks(self) -> Vec<mir::Block> {
+        self.blocks
+    }
+
+    #[must_use]
+    pub fn into_remarks(self) -> Remarks {
+        self.remarks
+    }
+
+    /// The changes made since the statistics were last taken
+    pub fn take_statistics(&mut self) -> Statistics {
+        std::mem::take(&mut self.statistics)
+    }
+
+    /// Add a new block to the end of the program, returning its index
+    pub fn add_block(&mut self, block: mir::Block) -> usize {
+        self.statistics.added += block.instrs.len();
+        self.blocks.push(block);
+        self.blocks.len() - 1
+    }
+
+    /// Replace the instructions of a block, returning whether they changed
+    pub fn replace_instrs(&mut self, index: usize, instrs: Vec<Span<mir::Instr>>) -> bool {
+        if instrs == self.blocks[index].instrs {
+            return false;
+        }
+
+        self.statistics += Statistics::compare(&self.blocks[index].instrs, &instrs);
+        self.blocks[index].instrs = instrs;
+        true
+    }
+
+    /// The block previously made for calling the given block with the given leading arguments
+    #[must_use]
+    pub fn find_specialisation(&self, block: usize, args: &[mir::Value]) -> Option<usize> {
+        self.specialisations
+            .iter()
+            .find(|specialisation| specialisation.block == block && specialisation.args == args)
+            .map(|specialisation| specialisation.specialised)
+    }
+
+    /// How many specialisations have been made of the given block
+    #[must_use]
+    pub fn specialisation_count(&self, block: usize) -> usize {
+        self.specialisations
+            .iter()
+            .filter(|specialisation| specialisation.block == block)
+            .count()
+    }
+
+    pub fn add_specialisation(&mut self, specialisation: Specialisation) {
+        self.specialisations
+            .push(specialisation);
+    }
+
+    /// Remove the given blocks and renumber the rest
+    ///
+    /// # Panics
+    ///
+    /// Panics if the entry block is removed, or if a remaining block still refers to a removed one
+    pub fn remove_blocks(&mut self, removed: &BTreeSet<usize>) {
+        assert!(!removed.contains(&0), "the entry block can't be removed");
+
+        let mut next = 0;
+        let indices = (0..self.blocks.len())
+            .map(|index| {
+                if removed.contains(&index) {
+                    None
+                } else {
+                    next += 1;
+                    Some(next - 1)
+                }
+            })
+            .collect::<Vec<_>>();
+
+        // A block nested within a removed block now belongs to the closest ancestor which remains
+        let parents = self
+            .blocks
+            .iter()
+            .map(|block| {
+                let mut parent = block.source.parent;
+
+                while let Some(index) = parent.filter(|index| indices[*index].is_none()) {
+                    parent = self.blocks[index].source.parent;
+                }
+
+                parent.and_then(|index| indices[index])
+            })
+            .collect::<Vec<_>>();
+
+        self.statistics.removed += removed
+            .iter()
+            .map(|index| self.blocks[*index].instrs.len())
+            .sum::<usize>();
+
+        // Specialisations involving a removed block are forgotten, and would be made again if needed
+        let specialisations = std::mem::take(&mut self.specialisations);
+
+        self.specialisations = specialisations
+            .into_iter()
+            .filter(|specialisation| {
+                let mut blocks = BTreeSet::from([specialisation.block, specialisation.specialised]);
+
+                for arg in &specialisation.args {
+                    call_graph::value_blocks(arg, &mut blocks);
+                }
+
+                blocks.is_disjoint(removed)
+            })
+            .map(|specialisation| Specialisation {
+                block: indices[specialisation.block].unwrap(),
+                args: specialisation
+                    .args
+                    .iter()
+                    .map(|arg| renumber_value(arg, &indices))
+                    .collect(),
+                specialised: indices[specialisation.specialised].unwrap(),
+            })
+            .collect();
+
+        let blocks = std::mem::take(&mut self.blocks);
+
+        self.blocks = blocks
+            .into_iter()
+            .zip(parents)
+            .enumerate()
+            .filter(|(index, _)| indices[*index].is_some())
+            .map(|(_, (mut block, parent))| {
+                block.source.parent = parent;
+
+                for instr in &mut block.instrs {
+                    instr.data = renumber_instr(&instr.data, &indices);
+                }
+
+                block
+            })
+            .collect();
+    }
+
+    /// Rewrite the instructions of every block in turn, returning whether any of them changed
+    pub fn rewrite_blocks(&mut self, mut rewrite: impl FnMut(&mut BlockContext) -> Vec<Span<mir::Instr>>) -> bool {
+        let mut changed = false;
+
+        for index in 0..self.blocks.len() {
+            let instrs = rewrite(&mut BlockContext::new(&self.blocks, index, self.options, &mut self.remarks));
+            changed |= self.replace_instrs(index, instrs);
+        }
+
+        changed
     }
+}
 
-    pub fn current_instrs(&self) -> impl Iterator<Item = &Span<mir::Instr>> {
-        self.current.instrs.iter()
+impl<'a> BlockContext<'a> {
+    #[must_use]
+    pub fn new(blocks: &'a [mir::Block], current: usize, options: &'a Options, remarks: &'a mut Remarks) -> Self {
+        Self {
+            blocks,
+            current,
+            options,
+            remarks,
+        }
     }
 
+    #[must_use]
+    pub fn current_index(&self) -> usize {
+        self.current
+    }
+
+    pub fn current_instrs(&self) -> impl Iterator<Item = &'a Span<mir::Instr>> {
+        self.blocks[self.current].instrs.iter()
+    }
+
+    #[must_use]
     pub fn current_len(&self) -> usize {
-        self.current.instrs.len()
+        self.blocks[self.current].instrs.len()
+    }
+
+    #[must_use]
+    pub fn blocks(&self) -> &'a [mir::Block] {
+        self.blocks
+    }
+
+    #[must_use]
+    pub fn options(&self) -> &'a Options {
+        self.options
+    }
+
+    pub fn remarks(&mut self) -> &mut Remarks {
+        self.remarks
     }
 }
@@ -39,6 +39,8 @@ fn convert_block(hir: hir::Block) -> mir::Block {
             .map(convert_instr)
             .collect(),
         name: hir.name,
+        source: hir.source,
+        first_class: true,
     }
 }
 
@@ -1,64 +1,283 @@
+use std::fmt::Display;
+
 use catastrophic_core::profiling::TimeScope;
 use catastrophic_hir::hir;
 use catastrophic_mir::mir;
 
 use self::context::OptimizationContext;
 use self::pass::OptimizationPass;
+use self::remark::{Remark, Remarks};
 
-mod context;
+pub mod call_graph;
+pub mod context;
 mod convert;
-mod pass;
+pub mod pass;
+pub mod remark;
+pub mod statistics;
+
+/// A pass in the pipeline, which may be repeated until it stops making changes
+struct Step {
+    pass: Box<dyn OptimizationPass>,
+    repeat: bool,
+}
 
 pub struct Options {
-    passes: Vec<Box<dyn OptimizationPass>>,
+    steps: Vec<Step>,
+    /// Whether the whole pipeline is repeated until none of its passes make any more changes
+    repeat_pipeline: bool,
+    inline_threshold: usize,
+    evaluation_budget: usize,
 }
 
 pub struct Optimizer;
 
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UnknownPass(pub String);
+
+/// A pass which left the program malformed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PassError {
+    pub pass: &'static str,
+    pub error: mir::VerifyError,
+}
+
+impl Step {
+    fn new(pass: Box<dyn OptimizationPass>) -> Self {
+        Self { pass, repeat: false }
+    }
+}
+
 impl Options {
+    /// The default maximum number of instructions in a block for it to be inlined
+    pub const DEFAULT_INLINE_THRESHOLD: usize = 8;
+
+    /// The default number of instructions partial evaluation may run before giving up
+    pub const DEFAULT_EVALUATION_BUDGET: usize = 1_000_000;
+
+    fn new(steps: Vec<Step>, repeat_pipeline: bool) -> Self {
+        Self {
+            steps,
+            repeat_pipeline,
+            inline_threshold: Self::DEFAULT_INLINE_THRESHOLD,
+            evaluation_budget: Self::DEFAULT_EVALUATION_BUDGET,
+        }
+    }
+
     #[must_use]
     pub fn no_passes() -> Self {
-        Self { passes: Vec::new() }
+        Self::new(Vec::new(), false)
     }
 
+    /// Every pass which isn't optional, with the whole pipeline repeated until none of them make any more changes
     #[must_use]
     pub fn all_passes() -> Self {
-        Self { passes: pass::passes() }
+        Self::new(
+            pass::passes()
+                .into_iter()
+                .filter(|pass| !pass.is_optional())
+                .map(Step::new)
+                .collect(),
+            true,
+        )
+    }
+
+    /// Build a pipeline from a comma separated list of passes, which are each run once in order, except that a pass ending in
+    /// `*` is repeated until it stops making changes
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if any of the passes do not exist
+    pub fn from_spec(spec: &str) -> Result<Self, UnknownPass> {
+        spec.split(',')
+            .map(str::trim)
+            .filter(|name| !name.is_empty())
+            .map(|name| {
+                let (name, repeat) = match name.strip_suffix('*') {
+                    Some(name) => (name.trim_end(), true),
+                    None => (name, false),
+                };
+
+                pass::find_pass(name)
+                    .map(|pass| Step { pass, repeat })
+                    .ok_or_else(|| UnknownPass(name.to_owned()))
+            })
+            .collect::<Result<_, _>>()
+            .map(|steps| Self::new(steps, false))
+    }
+
+    /// Add a pass to the end of the pipeline, such as one which is left out by default
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pass does not exist
+    pub fn with_pass(mut self, name: &str) -> Result<Self, UnknownPass> {
+        let pass = pass::find_pass(name).ok_or_else(|| UnknownPass(name.to_owned()))?;
+        self.steps.push(Step::new(pass));
+        Ok(self)
+    }
+
+    /// Remove every occurrence of a pass from the pipeline
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the pass does not exist
+    pub fn without_pass(mut self, name: &str) -> Result<Self, UnknownPass> {
+        let Some(skipped) = pass::find_pass(name) else {
+            return Err(UnknownPass(name.to_owned()));
+        };
+
+        self.steps
+            .retain(|step| step.pass.name() != skipped.name());
+
+        Ok(self)
     }
 
     #[must_use]
-    pub fn without_pass(name: &str) -> Self {
-        Self {
-            passes: pass::passes()
-                .into_iter()
-                .filter(|pass| pass.name() != name)
-                .collect(),
-        }
+    pub fn with_inline_threshold(mut self, threshold: usize) -> Self {
+        self.inline_threshold = threshold;
+        self
+    }
+
+    #[must_use]
+    pub fn inline_threshold(&self) -> usize {
+        self.inline_threshold
+    }
+
+    #[must_use]
+    pub fn with_evaluation_budget(mut self, budget: usize) -> Self {
+        self.evaluation_budget = budget;
+        self
+    }
+
+    #[must_use]
+    pub fn evaluation_budget(&self) -> usize {
+        self.evaluation_budget
     }
 }
 
 impl Optimizer {
+    /// A limit on how many times the passes are repeated, in case they never settle
+    const MAX_ITERATIONS: usize = 16;
+
     #[must_use]
-    pub fn pass_names() -> Vec<&'static str> {
-        pass::pass_names()
+    pub fn pass_ids() -> Vec<String> {
+        pass::pass_ids()
+    }
+
+    /// # Errors
+    ///
+    /// Returns an error if a pass leaves the program malformed, which is only checked in debug builds
+    pub fn optimize_hir<'a, 'b: 'a>(
+        options: &Options,
+        higher_ir: Vec<hir::Block>,
+        time_scope: &'a mut TimeScope<'b>,
+    ) -> Result<Vec<mir::Block>, PassError> {
+        let middle_ir = {
+            let _scope = time_scope.scope(&"Conversion");
+            convert::convert_blocks(higher_ir)
+        };
+
+        Self::optimize_mir(options, middle_ir, time_scope)
     }
 
-    pub fn optimize_hir<'a, 'b: 'a>(options: Options, higher_ir: Vec<hir::Block>, time_scope: &'a mut TimeScope<'b>) -> Vec<mir::Block> {
-        let mut middle_ir = {
+    /// Optimize the program as usual, but collect every rewrite the passes applied or declined along the way
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a pass leaves the program malformed, which is only checked in debug builds
+    pub fn remarks<'a, 'b: 'a>(options: &Options, higher_ir: Vec<hir::Block>, time_scope: &'a mut TimeScope<'b>) -> Result<Vec<Remark>, PassError> {
+        let middle_ir = {
             let _scope = time_scope.scope(&"Conversion");
             convert::convert_blocks(higher_ir)
         };
 
-        for pass in options.passes {
-            let _scope = time_scope.scope(&format!("{} Pass", &pass.name()));
+        let mut context = OptimizationContext::with_remarks(middle_ir, options, Remarks::new(true));
+        Self::check(&context, "Conversion")?;
+        Self::run_steps(options, &mut context, time_scope)?;
+        Ok(context.into_remarks().into_remarks())
+    }
+
+    fn run_step(step: &Step, context: &mut OptimizationContext) -> Result<bool, PassError> {
+        context
+            .remarks()
+            .set_pass(step.pass.name());
+
+        let changed = step.pass.run(context);
+        Self::check(context, step.pass.name())?;
+
+        if step.repeat && changed {
+            for _ in 1..Self::MAX_ITERATIONS {
+                let changed = step.pass.run(context);
+                Self::check(context, step.pass.name())?;
+
+                if !changed {
+                    break;
+                }
+            }
+        }
+
+        Ok(changed)
+    }
+
+    /// Make sure the program is still well formed, which is only worth the time in debug builds
+    fn check(context: &OptimizationContext, pass: &'static str) -> Result<(), PassError> {
+        if cfg!(debug_assertions) {
+            context.verify(pass)
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Run every pass in the pipeline in turn
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a pass leaves the program malformed, which is only checked in debug builds
+    pub fn optimize_mir<'a, 'b: 'a>(
+        options: &Options,
+        middle_ir: Vec<mir::Block>,
+        time_scope: &'a mut TimeScope<'b>,
+    ) -> Result<Vec<mir::Block>, PassError> {
+        let mut context = OptimizationContext::new(middle_ir, options);
+        Self::check(&context, "Conversion")?;
+        Self::run_steps(options, &mut context, time_scope)?;
+        Ok(context.into_blocks())
+    }
+
+    fn run_steps<'a, 'b: 'a>(options: &Options, context: &mut OptimizationContext, time_scope: &'a mut TimeScope<'b>) -> Result<(), PassError> {
+        let iterations = if options.repeat_pipeline { Self::MAX_ITERATIONS } else { 1 };
+
+        for iteration in 1..=iterations {
+            let mut scope = time_scope.scope(&format!("Iteration {iteration}"));
+            let mut changed = false;
+
+            for step in &options.steps {
+                let mut scope = scope.scope(&format!("{} Pass", &step.pass.name()));
+                changed |= Self::run_step(step, context)?;
+                scope.note(&context.take_statistics());
+            }
 
-            for input in &mut middle_ir {
-                let context = OptimizationContext::new(input);
-                let instrs = pass.run(&context);
-                input.instrs = instrs;
+            if !changed {
+                break;
             }
         }
 
-        middle_ir
+        Ok(())
     }
 }
+
+impl Display for UnknownPass {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Unknown optimization pass `{}`", self.0)
+    }
+}
+
+impl std::error::Error for UnknownPass {}
+
+impl Display for PassError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} produced malformed MIR: {}", self.pass, self.error)
+    }
+}
+
+impl std::error::Error for PassError {}
@@ -0,0 +1,151 @@
+use catastrophic_core::span::Span;
+use catastrophic_mir::mir;
+
+use crate::optimizer::{
+    call_graph::CallGraph,
+    context::{BlockContext, OptimizationContext},
+    remark::describe_function,
+};
+
+use super::{constant_folding::can_trap, OptimizationPass};
+
+pub struct BlockInlining;
+
+// Values are re-evaluated wherever their argument is used, so they must not have any observable effect, which includes
+// stopping the program with a runtime error
+fn is_pure(value: &mir::Value) -> bool {
+    let pure = match value {
+        mir::Value::ImmediateBinOp(mir::BinOp::Random, _, _) => false,
+        mir::Value::ImmediateBinOp(_, x, y) => is_pure(x) && is_pure(y),
+        mir::Value::ImmediateTriOp(_, x, y, z) => is_pure(x) && is_pure(y) && is_pure(z),
+        mir::Value::Arg(_) | mir::Value::Number(_) | mir::Value::Function(_) => true,
+    };
+
+    pure && !can_trap(value)
+}
+
+fn remap_value(value: &mir::Value, offset: usize, args: &[mir::Value]) -> mir::Value {
+    match value {
+        mir::Value::Arg(index) if *index >= offset => args[index - offset].clone(),
+        mir::Value::ImmediateBinOp(op, x, y) => {
+            mir::Value::ImmediateBinOp(*op, Box::new(remap_value(x, offset, args)), Box::new(remap_value(y, offset, args)))
+        }
+        mir::Value::ImmediateTriOp(op, x, y, z) => mir::Value::ImmediateTriOp(
+            *op,
+            Box::new(remap_value(x, offset, args)),
+            Box::new(remap_value(y, offset, args)),
+            Box::new(remap_value(z, offset, args)),
+        ),
+        value => value.clone(),
+    }
+}
+
+impl BlockInlining {
+    // A block can be inlined if it is small, can't reach itself, and only captures arguments its caller shares
+    fn can_inline(context: &BlockContext, call_graph: &CallGraph, index: usize) -> Result<(), &'static str> {
+        let blocks = context.blocks();
+        let callee = &blocks[index];
+
+        if index == context.current_index() || call_graph.is_recursive(index) {
+            Err("recursive")
+        } else if callee.instrs.len() > context.options().inline_threshold() {
+            Err("too large")
+        } else if call_graph
+            .references(index)
+            .iter()
+            .any(|&referenced| blocks[referenced].offset > callee.offset)
+        {
+            Err("captures arguments of its own")
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Splice the callee into the caller's instructions, replacing the pushes of its arguments
+    fn inline(context: &BlockContext, call_graph: &CallGraph, index: usize, instrs: &mut Vec<Span<mir::Instr>>) -> Result<(), &'static str> {
+        let callee = &context.blocks()[index];
+
+        Self::can_inline(context, call_graph, index)?;
+
+        if callee.args > instrs.len() {
+            return Err("arguments not known");
+        }
+
+        // The callee's first argument is the top of the stack, so the most recent push
+        let args = instrs
+            .iter()
+            .rev()
+            .take(callee.args)
+            .map(|instr| match &instr.data {
+                mir::Instr::Push(value) if is_pure(value) => Some(value.clone()),
+                _ => None,
+            })
+            .collect::<Option<Vec<_>>>();
+
+        let Some(args) = args else {
+            return Err("arguments not known");
+        };
+
+        instrs.truncate(instrs.len() - callee.args);
+        instrs.extend(callee.instrs.iter().map(|instr| {
+            instr.swap(match &instr.data {
+                mir::Instr::Push(value) => mir::Instr::Push(remap_value(value, callee.offset, &args)),
+                // The callee's tail calls may no longer be at the end once inlined, so become plain calls
+                mir::Instr::ImmediateConditionalCall(value, x, y) | mir::Instr::ConditionalTailCall(value, x, y) => {
+                    mir::Instr::ImmediateConditionalCall(remap_value(value, callee.offset, &args), *x, *y)
+                }
+                mir::Instr::TailCall(function) => mir::Instr::ImmediateCall(*function),
+                instr => instr.clone(),
+            })
+        }));
+
+        Ok(())
+    }
+}
+
+impl OptimizationPass for BlockInlining {
+    fn name(&self) -> &'static str {
+        "Block Inlining"
+    }
+
+    fn run(&self, context: &mut OptimizationContext) -> bool {
+        // Inlining never makes a block reachable from one it wasn't before, so the graph is safe to reuse for every block
+        let call_graph = context.call_graph();
+
+        context.rewrite_blocks(|context| {
+            let mut instrs: Vec<Span<mir::Instr>> = Vec::with_capacity(context.current_len());
+
+            let blocks = context.blocks();
+
+            for instr in context.current_instrs() {
+                let inlined = match instr.data {
+                    mir::Instr::ImmediateCall(mir::Function::Block(index)) | mir::Instr::TailCall(mir::Function::Block(index)) => {
+                        let name = || describe_function(blocks, mir::Function::Block(index));
+
+                        match Self::inline(context, &call_graph, index, &mut instrs) {
+                            Ok(()) => {
+                                context
+                                    .remarks()
+                                    .applied(instr.swap(()), || format!("inlined {}", name()));
+                                true
+                            }
+                            Err(reason) => {
+                                context
+                                    .remarks()
+                                    .declined(instr.swap(()), || format!("could not inline {}: {reason}", name()));
+                                false
+                            }
+                        }
+                    }
+                    _ => false,
+                };
+
+                if !inlined {
+                    instrs.push(instr.clone());
+                }
+            }
+
+            instrs
+        })
+    }
+}
@@ -0,0 +1,236 @@
+use std::collections::{BTreeMap, BTreeSet};
+
+use catastrophic_core::span::Span;
+use catastrophic_mir::mir;
+
+use crate::optimizer::{
+    call_graph::CallGraph,
+    context::{OptimizationContext, Specialisation},
+    remark::describe_function,
+};
+
+use super::{constant_folding::fold_instr, OptimizationPass};
+
+pub struct BlockSpecialisation;
+
+/// The most copies made of any one block, so that many call sites with different constants can't bloat the program
+const MAX_SPECIALISATIONS: usize = 8;
+
+// A block captures the arguments of wherever it's pushed, so can only be moved into the callee if it captures nothing
+fn is_constant(blocks: &[mir::Block], value: &mir::Value) -> bool {
+    match value {
+        mir::Value::Number(_) | mir::Value::Function(mir::Function::BinOp(_) | mir::Function::TriOp(_)) => true,
+        mir::Value::Function(mir::Function::Block(index)) => blocks[*index].offset == 0,
+        mir::Value::Arg(_) | mir::Value::ImmediateBinOp(..) | mir::Value::ImmediateTriOp(..) => false,
+    }
+}
+
+/// Replaces the leading arguments of a block with constants, throughout the block and every block nested within it
+struct Substitution<'a> {
+    /// The index of the first argument being replaced
+    position: usize,
+    args: &'a [mir::Value],
+    /// The copies made of each nested block, so references between them stay within the copies
+    clones: BTreeMap<usize, usize>,
+}
+
+impl Substitution<'_> {
+    // The remaining arguments move down to take the place of those which were replaced
+    fn value(&self, value: &mir::Value) -> mir::Value {
+        match value {
+            mir::Value::Arg(index) if *index >= self.position => self
+                .args
+                .get(index - self.position)
+                .cloned()
+                .unwrap_or_else(|| mir::Value::Arg(index - self.args.len())),
+            mir::Value::Function(function) => mir::Value::Function(self.function(*function)),
+            mir::Value::ImmediateBinOp(op, x, y) => mir::Value::ImmediateBinOp(*op, Box::new(self.value(x)), Box::new(self.value(y))),
+            mir::Value::ImmediateTriOp(op, x, y, z) => {
+                mir::Value::ImmediateTriOp(*op, Box::new(self.value(x)), Box::new(self.value(y)), Box::new(self.value(z)))
+            }
+            value => value.clone(),
+        }
+    }
+
+    fn function(&self, function: mir::Function) -> mir::Function {
+        match function {
+            mir::Function::Block(index) => mir::Function::Block(
+                *self
+                    .clones
+                    .get(&index)
+                    .unwrap_or(&index),
+            ),
+            function => function,
+        }
+    }
+
+    fn instr(&self, instr: &mir::Instr) -> mir::Instr {
+        match instr {
+            mir::Instr::Push(value) => mir::Instr::Push(self.value(value)),
+            mir::Instr::ImmediateCall(function) => mir::Instr::ImmediateCall(self.function(*function)),
+            mir::Instr::ImmediateConditionalCall(value, x, y) => {
+                mir::Instr::ImmediateConditionalCall(self.value(value), self.function(*x), self.function(*y))
+            }
+            mir::Instr::TailCall(function) => mir::Instr::TailCall(self.function(*function)),
+            mir::Instr::ConditionalTailCall(value, x, y) => mir::Instr::ConditionalTailCall(self.value(value), self.function(*x), self.function(*y)),
+            mir::Instr::Command(command) => mir::Instr::Command(*command),
+        }
+    }
+}
+
+impl BlockSpecialisation {
+    /// Make a copy of the block with its leading arguments replaced by the given constants, returning its index
+    ///
+    /// Blocks nested within it which capture those arguments are copied too, as they expect the arguments to be laid out as they were
+    ///
+    /// The copies are added to the call graph as they're made, so it stays up to date for the rest of the run
+    fn specialise(context: &mut OptimizationContext, call_graph: &mut CallGraph, index: usize, args: Vec<mir::Value>) -> usize {
+        let blocks = context.blocks();
+        let position = blocks[index].offset;
+
+        let mut nested = BTreeSet::new();
+        let mut queue = vec![index];
+
+        while let Some(next) = queue.pop() {
+            for &referenced in call_graph.references(next) {
+                if blocks[referenced].offset > position && nested.insert(referenced) {
+                    queue.push(referenced);
+                }
+            }
+        }
+
+        // The block's own recursive calls pass it new arguments, so keep calling the original
+        let specialised = blocks.len();
+        let substitution = Substitution {
+            position,
+            args: &args,
+            clones: nested
+                .iter()
+                .enumerate()
+                .map(|(clone, &original)| (original, specialised + 1 + clone))
+                .collect(),
+        };
+
+        let count = context.specialisation_count(index);
+
+        for original in std::iter::once(index).chain(nested) {
+            let block = context.blocks()[original].clone();
+
+            let instrs = block
+                .instrs
+                .iter()
+                .map(|instr| fold_instr(&instr.swap(substitution.instr(&instr.data)), context.remarks()))
+                .collect();
+
+            let mut source = block.source;
+            source.parent = source.parent.map(|parent| {
+                if parent == index {
+                    specialised
+                } else {
+                    *substitution
+                        .clones
+                        .get(&parent)
+                        .unwrap_or(&parent)
+                }
+            });
+
+            let copy = mir::Block {
+                offset: if original == index { block.offset } else { block.offset - args.len() },
+                args: if original == index { block.args - args.len() } else { block.args },
+                instrs,
+                name: format!("{}_specialised_{count}", block.name),
+                source,
+                first_class: original != index && block.first_class,
+            };
+
+            call_graph.add_block(&copy);
+            context.add_block(copy);
+        }
+
+        context.add_specialisation(Specialisation {
+            block: index,
+            args,
+            specialised,
+        });
+
+        specialised
+    }
+}
+
+impl OptimizationPass for BlockSpecialisation {
+    fn name(&self) -> &'static str {
+        "Block Specialisation"
+    }
+
+    fn run(&self, context: &mut OptimizationContext) -> bool {
+        let mut call_graph = context.call_graph();
+        let mut changed = false;
+
+        // Only the blocks which existed beforehand are rewritten, as any copies made along the way are already specialised
+        for index in 0..context.blocks().len() {
+            let mut instrs: Vec<Span<mir::Instr>> = Vec::with_capacity(context.blocks()[index].instrs.len());
+
+            for instr in context.blocks()[index].instrs.clone() {
+                let (mir::Instr::ImmediateCall(mir::Function::Block(callee)) | mir::Instr::TailCall(mir::Function::Block(callee))) = instr.data
+                else {
+                    instrs.push(instr);
+                    continue;
+                };
+
+                // The callee's first argument is the top of the stack, so the most recent push
+                let args = instrs
+                    .iter()
+                    .rev()
+                    .take(context.blocks()[callee].args)
+                    .map_while(|instr| match &instr.data {
+                        mir::Instr::Push(value) if is_constant(context.blocks(), value) => Some(value.clone()),
+                        _ => None,
+                    })
+                    .collect::<Vec<_>>();
+
+                if args.is_empty() {
+                    instrs.push(instr);
+                    continue;
+                }
+
+                let name = describe_function(context.blocks(), mir::Function::Block(callee));
+                let count = args.len();
+
+                let specialised = if let Some(specialised) = context.find_specialisation(callee, &args) {
+                    specialised
+                } else if context.specialisation_count(callee) >= MAX_SPECIALISATIONS {
+                    context
+                        .remarks()
+                        .declined(instr.swap(()), || format!("could not specialise {name}: too many specialisations"));
+                    instrs.push(instr);
+                    continue;
+                } else {
+                    Self::specialise(context, &mut call_graph, callee, args)
+                };
+
+                instrs.truncate(instrs.len() - count);
+                instrs.push(instr.swap(match instr.data {
+                    mir::Instr::TailCall(_) => mir::Instr::TailCall(mir::Function::Block(specialised)),
+                    _ => mir::Instr::ImmediateCall(mir::Function::Block(specialised)),
+                }));
+
+                context
+                    .remarks()
+                    .applied(instr.swap(()), || {
+                        format!(
+                            "specialised {name} for {count} constant {}",
+                            if count == 1 { "argument" } else { "arguments" }
+                        )
+                    });
+            }
+
+            // Blocks specialised later in the run may be nested within this one, so have to see its calls to any copies
+            if context.replace_instrs(index, instrs) {
+                call_graph.update_block(index, &context.blocks()[index]);
+                changed = true;
+            }
+        }
+
+        changed
+    }
+}
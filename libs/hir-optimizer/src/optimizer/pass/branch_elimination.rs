@@ -0,0 +1,103 @@
+use catastrophic_core::{defines::ValueType, span::Span};
+use catastrophic_mir::mir;
+
+use crate::optimizer::{
+    context::{BlockContext, OptimizationContext},
+    remark::{describe_function, Remarks},
+};
+
+use super::{constant_folding::can_trap, OptimizationPass};
+
+pub struct BranchElimination;
+
+// Both branches of `?` are worked out before one is picked, so the one not taken can only be removed if it can't fail
+fn eliminate_branches(value: &mir::Value, span: Span<()>, remarks: &mut Remarks) -> mir::Value {
+    match value {
+        mir::Value::ImmediateTriOp(mir::TriOp::IfThenElse, condition, x, y) => match eliminate_branches(condition, span, remarks) {
+            mir::Value::Number(condition) if condition == ValueType::from(false) && !can_trap(x) => {
+                remarks.applied(span, || "removed `?` whose condition is always false");
+                eliminate_branches(y, span, remarks)
+            }
+            mir::Value::Number(condition) if condition != ValueType::from(false) && !can_trap(y) => {
+                remarks.applied(span, || "removed `?` whose condition is always true");
+                eliminate_branches(x, span, remarks)
+            }
+            condition => {
+                if let mir::Value::Number(_) = condition {
+                    remarks.declined(span, || "could not remove `?`: the branch not taken could fail");
+                }
+
+                mir::Value::ImmediateTriOp(
+                    mir::TriOp::IfThenElse,
+                    Box::new(condition),
+                    Box::new(eliminate_branches(x, span, remarks)),
+                    Box::new(eliminate_branches(y, span, remarks)),
+                )
+            }
+        },
+        mir::Value::ImmediateBinOp(op, x, y) => mir::Value::ImmediateBinOp(
+            *op,
+            Box::new(eliminate_branches(x, span, remarks)),
+            Box::new(eliminate_branches(y, span, remarks)),
+        ),
+        value => value.clone(),
+    }
+}
+
+/// Pick the branch of a conditional call which is always taken, if the condition is known
+fn eliminate_call(
+    value: &mir::Value,
+    x: mir::Function,
+    y: mir::Function,
+    span: Span<()>,
+    context: &mut BlockContext,
+) -> Result<mir::Function, mir::Value> {
+    let blocks = context.blocks();
+
+    match eliminate_branches(value, span, context.remarks()) {
+        mir::Value::Number(condition) => {
+            let (taken, skipped) = if condition == ValueType::from(false) { (y, x) } else { (x, y) };
+
+            context.remarks().applied(span, || {
+                format!(
+                    "always calls {} rather than {}",
+                    describe_function(blocks, taken),
+                    describe_function(blocks, skipped)
+                )
+            });
+
+            Ok(taken)
+        }
+        value => Err(value),
+    }
+}
+
+impl OptimizationPass for BranchElimination {
+    fn name(&self) -> &'static str {
+        "Branch Elimination"
+    }
+
+    fn run(&self, context: &mut OptimizationContext) -> bool {
+        context.rewrite_blocks(|context| {
+            context
+                .current_instrs()
+                .map(|instr| {
+                    let span = instr.swap(());
+
+                    instr.swap(match &instr.data {
+                        mir::Instr::Push(value) => mir::Instr::Push(eliminate_branches(value, span, context.remarks())),
+                        mir::Instr::ImmediateConditionalCall(value, x, y) => match eliminate_call(value, *x, *y, span, context) {
+                            Ok(function) => mir::Instr::ImmediateCall(function),
+                            Err(value) => mir::Instr::ImmediateConditionalCall(value, *x, *y),
+                        },
+                        mir::Instr::ConditionalTailCall(value, x, y) => match eliminate_call(value, *x, *y, span, context) {
+                            Ok(function) => mir::Instr::TailCall(function),
+                            Err(value) => mir::Instr::ConditionalTailCall(value, *x, *y),
+                        },
+                        instr => instr.clone(),
+                    })
+                })
+                .collect()
+        })
+    }
+}
@@ -0,0 +1,123 @@
+use catastrophic_core::{defines::ValueType, span::Span};
+use catastrophic_mir::mir;
+
+use crate::optimizer::{
+    context::OptimizationContext,
+    remark::{bin_op_symbol, tri_op_symbol, Remarks},
+};
+
+use super::OptimizationPass;
+
+pub struct ConstantFolding;
+
+// Operations which would overflow or divide by zero are left for the runtime to handle as it sees fit
+fn fold_bin_op(op: mir::BinOp, x: ValueType, y: ValueType) -> Result<ValueType, &'static str> {
+    match op {
+        mir::BinOp::Plus => x
+            .checked_add(y)
+            .ok_or("it would overflow"),
+        mir::BinOp::Minus => x
+            .checked_sub(y)
+            .ok_or("it would overflow"),
+        mir::BinOp::Multiply => x
+            .checked_mul(y)
+            .ok_or("it would overflow"),
+        mir::BinOp::Divide if y == 0 => Err("it divides by zero"),
+        mir::BinOp::Divide => x
+            .checked_div(y)
+            .ok_or("it would overflow"),
+        mir::BinOp::Equals => Ok(ValueType::from(x == y)),
+        mir::BinOp::GreaterThan => Ok(ValueType::from(x > y)),
+        mir::BinOp::LessThan => Ok(ValueType::from(x < y)),
+        mir::BinOp::Random => Err("its result is random"),
+    }
+}
+
+/// Whether working out the value could stop the program with a runtime error, such as by overflowing or dividing by zero
+///
+/// Only operations on constants which would fold are known not to, as anything else depends on values only known at runtime
+pub(super) fn can_trap(value: &mir::Value) -> bool {
+    match value {
+        mir::Value::ImmediateBinOp(op, x, y) => {
+            let traps = match (op, x.as_ref(), y.as_ref()) {
+                (mir::BinOp::Equals | mir::BinOp::GreaterThan | mir::BinOp::LessThan, _, _) => false,
+                (mir::BinOp::Random, mir::Value::Number(a), mir::Value::Number(b)) => a > b,
+                (_, mir::Value::Number(a), mir::Value::Number(b)) => fold_bin_op(*op, *a, *b).is_err(),
+                _ => true,
+            };
+
+            traps || can_trap(x) || can_trap(y)
+        }
+        mir::Value::ImmediateTriOp(_, x, y, z) => can_trap(x) || can_trap(y) || can_trap(z),
+        mir::Value::Arg(_) | mir::Value::Number(_) | mir::Value::Function(_) => false,
+    }
+}
+
+fn fold_tri_op(op: mir::TriOp, x: ValueType, y: ValueType, z: ValueType) -> ValueType {
+    match op {
+        mir::TriOp::IfThenElse => {
+            if x == ValueType::from(false) {
+                z
+            } else {
+                y
+            }
+        }
+    }
+}
+
+fn fold_value(value: &mir::Value, span: Span<()>, remarks: &mut Remarks) -> mir::Value {
+    match value {
+        mir::Value::ImmediateBinOp(op, x, y) => {
+            let (x, y) = (fold_value(x, span, remarks), fold_value(y, span, remarks));
+
+            if let (mir::Value::Number(a), mir::Value::Number(b)) = (&x, &y) {
+                match fold_bin_op(*op, *a, *b) {
+                    Ok(result) => {
+                        remarks.applied(span, || format!("folded call to `{}`", bin_op_symbol(*op)));
+                        return mir::Value::Number(result);
+                    }
+                    Err(reason) => remarks.declined(span, || format!("could not fold call to `{}`: {reason}", bin_op_symbol(*op))),
+                }
+            }
+
+            mir::Value::ImmediateBinOp(*op, Box::new(x), Box::new(y))
+        }
+        mir::Value::ImmediateTriOp(op, x, y, z) => {
+            let (x, y, z) = (fold_value(x, span, remarks), fold_value(y, span, remarks), fold_value(z, span, remarks));
+
+            if let (mir::Value::Number(a), mir::Value::Number(b), mir::Value::Number(c)) = (&x, &y, &z) {
+                remarks.applied(span, || format!("folded call to `{}`", tri_op_symbol(*op)));
+                return mir::Value::Number(fold_tri_op(*op, *a, *b, *c));
+            }
+
+            mir::Value::ImmediateTriOp(*op, Box::new(x), Box::new(y), Box::new(z))
+        }
+        value => value.clone(),
+    }
+}
+
+pub(super) fn fold_instr(instr: &Span<mir::Instr>, remarks: &mut Remarks) -> Span<mir::Instr> {
+    let span = instr.swap(());
+
+    instr.swap(match &instr.data {
+        mir::Instr::Push(value) => mir::Instr::Push(fold_value(value, span, remarks)),
+        mir::Instr::ImmediateConditionalCall(value, x, y) => mir::Instr::ImmediateConditionalCall(fold_value(value, span, remarks), *x, *y),
+        mir::Instr::ConditionalTailCall(value, x, y) => mir::Instr::ConditionalTailCall(fold_value(value, span, remarks), *x, *y),
+        instr => instr.clone(),
+    })
+}
+
+impl OptimizationPass for ConstantFolding {
+    fn name(&self) -> &'static str {
+        "Constant Folding"
+    }
+
+    fn run(&self, context: &mut OptimizationContext) -> bool {
+        context.rewrite_blocks(|context| {
+            context
+                .current_instrs()
+                .map(|instr| fold_instr(instr, context.remarks()))
+                .collect()
+        })
+    }
+}
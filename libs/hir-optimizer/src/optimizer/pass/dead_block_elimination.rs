@@ -0,0 +1,51 @@
+use std::collections::BTreeSet;
+
+use catastrophic_mir::mir;
+
+use crate::optimizer::{context::OptimizationContext, remark::describe_function};
+
+use super::OptimizationPass;
+
+pub struct DeadBlockElimination;
+
+impl OptimizationPass for DeadBlockElimination {
+    fn name(&self) -> &'static str {
+        "Dead Block Elimination"
+    }
+
+    fn run(&self, context: &mut OptimizationContext) -> bool {
+        let call_graph = context.call_graph();
+
+        // The entry block is called by the runtime, so is always live
+        let mut live = call_graph.reachable(0);
+        live.insert(0);
+
+        let dead = (0..context.blocks().len())
+            .filter(|index| !live.contains(index))
+            .collect::<BTreeSet<_>>();
+
+        let mut changed = !dead.is_empty();
+
+        for &index in &dead {
+            // Blocks the optimizer made itself have no source to point at
+            if let Some(span) = context.blocks()[index].source.span {
+                let name = describe_function(context.blocks(), mir::Function::Block(index));
+                context
+                    .remarks()
+                    .applied(span, || format!("removed unused block {name}"));
+            }
+        }
+
+        for index in live {
+            let first_class = call_graph.is_first_class(index);
+            let block = context.block_mut(index);
+
+            changed |= block.first_class != first_class;
+            block.first_class = first_class;
+        }
+
+        context.remove_blocks(&dead);
+
+        changed
+    }
+}
@@ -1,7 +1,7 @@
 use catastrophic_core::span::Span;
 use catastrophic_mir::mir;
 
-use crate::optimizer::context::OptimizationContext;
+use crate::optimizer::{context::OptimizationContext, remark::describe_function};
 
 use super::OptimizationPass;
 
@@ -12,25 +12,37 @@ impl OptimizationPass for ImmediateCalls {
         "Immediate Call"
     }
 
-    fn run(&self, context: &OptimizationContext) -> Vec<Span<mir::Instr>> {
-        let mut instrs: Vec<Span<mir::Instr>> = Vec::with_capacity(context.current_len());
-
-        for instr in context.current_instrs() {
-            if let mir::Instr::Command(mir::Command::Call) = instr.data {
-                if let Some(last) = instrs.last_mut() {
-                    let span = last.swap(());
-
-                    if let mir::Instr::Push(mir::Value::Function(function)) = last.data {
-                        *last = span.swap(mir::Instr::ImmediateCall(function));
+    fn run(&self, context: &mut OptimizationContext) -> bool {
+        context.rewrite_blocks(|context| {
+            let blocks = context.blocks();
+            let mut instrs: Vec<Span<mir::Instr>> = Vec::with_capacity(context.current_len());
+
+            for instr in context.current_instrs() {
+                if let mir::Instr::Command(mir::Command::Call) = instr.data {
+                    if let Some(last) = instrs.last_mut() {
+                        let span = last.swap(());
+
+                        if let mir::Instr::Push(mir::Value::Function(function)) = last.data {
+                            *last = span.swap(mir::Instr::ImmediateCall(function));
+                            context
+                                .remarks()
+                                .applied(span, || format!("called {} directly", describe_function(blocks, function)));
+                        } else {
+                            instrs.push(instr.clone());
+                            context
+                                .remarks()
+                                .declined(instr.swap(()), || "could not call directly: the function is only known at runtime");
+                        }
                     } else {
+                        // Nothing has been pushed in this block yet, so the function comes from the caller and must still be called
                         instrs.push(instr.clone());
                     }
+                } else {
+                    instrs.push(instr.clone());
                 }
-            } else {
-                instrs.push(instr.clone());
             }
-        }
 
-        instrs
+            instrs
+        })
     }
 }
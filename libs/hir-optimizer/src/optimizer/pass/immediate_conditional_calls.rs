@@ -3,7 +3,7 @@ use std::ops::Deref;
 use catastrophic_core::span::Span;
 use catastrophic_mir::mir;
 
-use crate::optimizer::context::OptimizationContext;
+use crate::optimizer::{context::OptimizationContext, remark::describe_function};
 
 use super::OptimizationPass;
 
@@ -14,29 +14,42 @@ impl OptimizationPass for ImmediateConditionalCalls {
         "Immediate Conditional Call"
     }
 
-    fn run(&self, context: &OptimizationContext) -> Vec<Span<mir::Instr>> {
-        let mut instrs: Vec<Span<mir::Instr>> = Vec::with_capacity(context.current_len());
-
-        for instr in context.current_instrs() {
-            if let mir::Instr::Command(mir::Command::Call) = instr.data {
-                if let Some(last) = instrs.last_mut() {
-                    let span = last.swap(());
-
-                    match &last.data {
-                        mir::Instr::Push(mir::Value::ImmediateTriOp(mir::TriOp::IfThenElse, value, x, y)) => match (&**x, &**y) {
-                            (&mir::Value::Function(a), &mir::Value::Function(b)) => {
-                                *last = span.swap(mir::Instr::ImmediateConditionalCall(value.deref().clone(), a, b));
+    fn run(&self, context: &mut OptimizationContext) -> bool {
+        context.rewrite_blocks(|context| {
+            let blocks = context.blocks();
+            let mut instrs: Vec<Span<mir::Instr>> = Vec::with_capacity(context.current_len());
+
+            for instr in context.current_instrs() {
+                if let mir::Instr::Command(mir::Command::Call) = instr.data {
+                    if let Some(last) = instrs.last_mut() {
+                        let span = last.swap(());
+
+                        match &last.data {
+                            mir::Instr::Push(mir::Value::ImmediateTriOp(mir::TriOp::IfThenElse, value, x, y)) => {
+                                if let (&mir::Value::Function(a), &mir::Value::Function(b)) = (&**x, &**y) {
+                                    *last = span.swap(mir::Instr::ImmediateConditionalCall(value.deref().clone(), a, b));
+                                    context.remarks().applied(span, || {
+                                        format!("called {} or {} directly", describe_function(blocks, a), describe_function(blocks, b))
+                                    });
+                                } else {
+                                    instrs.push(instr.clone());
+                                    context
+                                        .remarks()
+                                        .declined(instr.swap(()), || "could not call directly: a branch is only known at runtime");
+                                }
                             }
                             _ => instrs.push(instr.clone()),
-                        },
-                        _ => instrs.push(instr.clone()),
+                        }
+                    } else {
+                        // Nothing has been pushed in this block yet, so the function comes from the caller and must still be called
+                        instrs.push(instr.clone());
                     }
+                } else {
+                    instrs.push(instr.clone());
                 }
-            } else {
-                instrs.push(instr.clone());
             }
-        }
 
-        instrs
+            instrs
+        })
     }
 }
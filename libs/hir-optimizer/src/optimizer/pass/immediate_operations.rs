@@ -1,13 +1,26 @@
 use catastrophic_core::span::Span;
 use catastrophic_mir::mir;
 
-use crate::optimizer::context::OptimizationContext;
+use crate::optimizer::{
+    context::OptimizationContext,
+    remark::{bin_op_symbol, tri_op_symbol, Remarks},
+};
 
 use super::OptimizationPass;
 
 pub struct ImmediateOperations;
 
-fn run_pass<'a>(input: impl Iterator<Item = &'a Span<mir::Instr>>, len: usize) -> (bool, Vec<Span<mir::Instr>>) {
+fn remark(remarks: &mut Remarks, instr: &Span<mir::Instr>, symbol: &str, handled: bool) {
+    if handled {
+        remarks.applied(instr.swap(()), || format!("combined call to `{symbol}` with its arguments"));
+    } else {
+        remarks.declined(instr.swap(()), || {
+            format!("could not combine call to `{symbol}`: its arguments are not all known")
+        });
+    }
+}
+
+fn run_pass<'a>(input: impl Iterator<Item = &'a Span<mir::Instr>>, len: usize, remarks: &mut Remarks) -> (bool, Vec<Span<mir::Instr>>) {
     let mut instrs: Vec<Span<mir::Instr>> = Vec::with_capacity(len);
     let mut changes_made = false;
 
@@ -22,21 +35,24 @@ fn run_pass<'a>(input: impl Iterator<Item = &'a Span<mir::Instr>>, len: usize) -
                 let x = instrs.pop().unwrap();
                 let y = instrs.pop().unwrap();
 
-                if let (mir::Instr::Push(x), mir::Instr::Push(y)) = (&x.data, &y.data) {
+                let handled = if let (mir::Instr::Push(x), mir::Instr::Push(y)) = (&x.data, &y.data) {
                     instrs.push(instr.swap(mir::Instr::Push(mir::Value::ImmediateBinOp(op, Box::new(x.clone()), Box::new(y.clone())))));
                     true
                 } else {
                     instrs.push(y);
                     instrs.push(x);
                     false
-                }
+                };
+
+                remark(remarks, instr, bin_op_symbol(op), handled);
+                handled
             }
             mir::Instr::ImmediateCall(mir::Function::TriOp(op)) if instrs.len() >= 3 => {
                 let x = instrs.pop().unwrap();
                 let y = instrs.pop().unwrap();
                 let z = instrs.pop().unwrap();
 
-                if let (mir::Instr::Push(x), mir::Instr::Push(y), mir::Instr::Push(z)) = (&x.data, &y.data, &z.data) {
+                let handled = if let (mir::Instr::Push(x), mir::Instr::Push(y), mir::Instr::Push(z)) = (&x.data, &y.data, &z.data) {
                     instrs.push(instr.swap(mir::Instr::Push(mir::Value::ImmediateTriOp(
                         op,
                         Box::new(x.clone()),
@@ -49,7 +65,10 @@ fn run_pass<'a>(input: impl Iterator<Item = &'a Span<mir::Instr>>, len: usize) -
                     instrs.push(y);
                     instrs.push(x);
                     false
-                }
+                };
+
+                remark(remarks, instr, tri_op_symbol(op), handled);
+                handled
             }
 
             _ => false,
@@ -70,13 +89,15 @@ impl OptimizationPass for ImmediateOperations {
         "Immediate Operation"
     }
 
-    fn run(&self, context: &OptimizationContext) -> Vec<Span<mir::Instr>> {
-        let (mut changes_made, mut instrs) = run_pass(context.current_instrs(), context.current_len());
+    fn run(&self, context: &mut OptimizationContext) -> bool {
+        context.rewrite_blocks(|context| {
+            let (mut changes_made, mut instrs) = run_pass(context.current_instrs(), context.current_len(), context.remarks());
 
-        while changes_made {
-            (changes_made, instrs) = run_pass(instrs.iter(), instrs.len());
-        }
+            while changes_made {
+                (changes_made, instrs) = run_pass(instrs.iter(), instrs.len(), context.remarks());
+            }
 
-        instrs
+            instrs
+        })
     }
 }
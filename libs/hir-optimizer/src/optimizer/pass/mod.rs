@@ -1,30 +1,71 @@
-use catastrophic_core::span::Span;
-use catastrophic_mir::mir;
-
-use self::{immediate_calls::ImmediateCalls, immediate_conditional_calls::ImmediateConditionalCalls, immediate_operations::ImmediateOperations};
+use self::{
+    block_inlining::BlockInlining, block_specialisation::BlockSpecialisation, branch_elimination::BranchElimination,
+    constant_folding::ConstantFolding, dead_block_elimination::DeadBlockElimination, immediate_calls::ImmediateCalls,
+    immediate_conditional_calls::ImmediateConditionalCalls, immediate_operations::ImmediateOperations, partial_evaluation::PartialEvaluation,
+    tail_calls::TailCalls,
+};
 
 use super::context::OptimizationContext;
 
+mod block_inlining;
+mod block_specialisation;
+mod branch_elimination;
+mod constant_folding;
+mod dead_block_elimination;
 mod immediate_calls;
 mod immediate_conditional_calls;
 mod immediate_operations;
+mod partial_evaluation;
+mod tail_calls;
+mod test;
 
 pub trait OptimizationPass {
     fn name(&self) -> &'static str;
-    fn run(&self, context: &OptimizationContext) -> Vec<Span<mir::Instr>>;
+
+    /// The name used to refer to the pass on the command line
+    fn id(&self) -> String {
+        self.name()
+            .to_lowercase()
+            .replace(' ', "-")
+    }
+
+    /// Whether the pass is left out of the default pipeline, so only runs when asked for
+    fn is_optional(&self) -> bool {
+        false
+    }
+
+    /// Rewrite the program, returning whether anything was changed
+    fn run(&self, context: &mut OptimizationContext) -> bool;
 }
 
+#[must_use]
 pub fn passes() -> Vec<Box<dyn OptimizationPass>> {
     vec![
         Box::new(ImmediateCalls),
         Box::new(ImmediateOperations),
+        Box::new(ConstantFolding),
         Box::new(ImmediateConditionalCalls),
+        Box::new(BranchElimination),
+        Box::new(BlockInlining),
+        Box::new(BlockSpecialisation),
+        Box::new(TailCalls),
+        Box::new(DeadBlockElimination),
+        Box::new(PartialEvaluation),
     ]
 }
 
-pub fn pass_names() -> Vec<&'static str> {
+#[must_use]
+pub fn pass_ids() -> Vec<String> {
     passes()
         .into_iter()
-        .map(|pass| pass.name())
+        .map(|pass| pass.id())
         .collect()
 }
+
+/// Find a pass by either its name or its id
+#[must_use]
+pub fn find_pass(name: &str) -> Option<Box<dyn OptimizationPass>> {
+    passes()
+        .into_iter()
+        .find(|pass| pass.name() == name || pass.id() == name)
+}
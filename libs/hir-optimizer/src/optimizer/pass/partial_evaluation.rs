@@ -0,0 +1,378 @@
+use catastrophic_core::{defines::ValueType, span::Span};
+use catastrophic_mir::mir;
+
+use crate::optimizer::context::OptimizationContext;
+
+use super::OptimizationPass;
+
+pub struct PartialEvaluation;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+enum Value {
+    Number(ValueType),
+    BinOp(mir::BinOp),
+    TriOp(mir::TriOp),
+    Closure(usize),
+}
+
+#[derive(Debug, Clone)]
+struct Closure {
+    block: usize,
+    args: Vec<Value>,
+}
+
+#[derive(Debug, Copy, Clone)]
+enum Output {
+    Char(ValueType),
+    Number(ValueType),
+}
+
+struct Frame {
+    block: usize,
+    args: Vec<Value>,
+    instr: usize,
+}
+
+/// Why evaluation had to stop, leaving the rest of the program to run as usual
+#[derive(Debug, Copy, Clone)]
+enum Stop {
+    Input,
+    Random,
+    Budget,
+    /// Something which would fail or behave differently depending on how the program is run, like dividing by zero
+    Runtime,
+}
+
+/// Everything the program has done so far, which can be rolled back to a checkpoint
+struct Machine<'a> {
+    blocks: &'a [mir::Block],
+    stack: Vec<Value>,
+    closures: Vec<Closure>,
+    outputs: Vec<Span<Output>>,
+    steps: usize,
+}
+
+/// The state of the machine between two instructions of the entry block
+struct Checkpoint {
+    instr: usize,
+    stack: Vec<Value>,
+    outputs: usize,
+}
+
+impl Stop {
+    fn reason(self) -> &'static str {
+        match self {
+            Stop::Input => "reads input",
+            Stop::Random => "uses `!`",
+            Stop::Budget => "ran out of steps",
+            Stop::Runtime => "depends on runtime behaviour",
+        }
+    }
+}
+
+fn apply_bin_op(op: mir::BinOp, x: Value, y: Value) -> Result<Value, Stop> {
+    let (Value::Number(x), Value::Number(y)) = (x, y) else {
+        return Err(Stop::Runtime);
+    };
+
+    match op {
+        mir::BinOp::Plus => x.checked_add(y),
+        mir::BinOp::Minus => x.checked_sub(y),
+        mir::BinOp::Multiply => x.checked_mul(y),
+        mir::BinOp::Divide => x.checked_div(y),
+        mir::BinOp::Equals => Some(ValueType::from(x == y)),
+        mir::BinOp::GreaterThan => Some(ValueType::from(x > y)),
+        mir::BinOp::LessThan => Some(ValueType::from(x < y)),
+        mir::BinOp::Random => return Err(Stop::Random),
+    }
+    .map(Value::Number)
+    .ok_or(Stop::Runtime)
+}
+
+fn apply_tri_op(op: mir::TriOp, x: Value, y: Value, z: Value) -> Result<Value, Stop> {
+    match (op, x) {
+        (mir::TriOp::IfThenElse, Value::Number(condition)) => Ok(if condition == ValueType::from(false) { z } else { y }),
+        (mir::TriOp::IfThenElse, _) => Err(Stop::Runtime),
+    }
+}
+
+impl<'a> Machine<'a> {
+    fn new(blocks: &'a [mir::Block], budget: usize) -> Self {
+        Self {
+            blocks,
+            stack: Vec::new(),
+            closures: Vec::new(),
+            outputs: Vec::new(),
+            steps: budget,
+        }
+    }
+
+    fn checkpoint(&self, instr: usize) -> Checkpoint {
+        Checkpoint {
+            instr,
+            stack: self.stack.clone(),
+            outputs: self.outputs.len(),
+        }
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack
+            .pop()
+            .unwrap_or(Value::Number(0))
+    }
+
+    fn value(&mut self, value: &mir::Value, args: &[Value]) -> Result<Value, Stop> {
+        Ok(match value {
+            mir::Value::Arg(index) => args[*index],
+            mir::Value::Number(value) => Value::Number(*value),
+            mir::Value::Function(function) => self.function(*function, args),
+            mir::Value::ImmediateBinOp(op, x, y) => {
+                let (x, y) = (self.value(x, args)?, self.value(y, args)?);
+                apply_bin_op(*op, x, y)?
+            }
+            mir::Value::ImmediateTriOp(op, x, y, z) => {
+                let (x, y, z) = (self.value(x, args)?, self.value(y, args)?, self.value(z, args)?);
+                apply_tri_op(*op, x, y, z)?
+            }
+        })
+    }
+
+    fn function(&mut self, function: mir::Function, args: &[Value]) -> Value {
+        match function {
+            mir::Function::Block(block) => {
+                self.closures.push(Closure {
+                    block,
+                    args: args[..self.blocks[block].offset].to_vec(),
+                });
+                Value::Closure(self.closures.len() - 1)
+            }
+            mir::Function::BinOp(op) => Value::BinOp(op),
+            mir::Function::TriOp(op) => Value::TriOp(op),
+        }
+    }
+
+    /// Call a function with arguments from the stack, returning the frame to run if it's a block
+    fn call(&mut self, function: Value) -> Result<Option<Frame>, Stop> {
+        match function {
+            Value::Number(_) => Err(Stop::Runtime),
+            Value::BinOp(op) => {
+                let (x, y) = (self.pop(), self.pop());
+                let result = apply_bin_op(op, x, y)?;
+                self.stack.push(result);
+                Ok(None)
+            }
+            Value::TriOp(op) => {
+                let (x, y, z) = (self.pop(), self.pop(), self.pop());
+                let result = apply_tri_op(op, x, y, z)?;
+                self.stack.push(result);
+                Ok(None)
+            }
+            Value::Closure(closure) => {
+                let Closure { block, mut args } = self.closures[closure].clone();
+
+                for _ in 0..self.blocks[block].args {
+                    let arg = self.pop();
+                    args.push(arg);
+                }
+
+                Ok(Some(Frame { block, args, instr: 0 }))
+            }
+        }
+    }
+
+    fn output(&mut self, span: Span<()>, output: fn(ValueType) -> Output) -> Result<(), Stop> {
+        match self.pop() {
+            Value::Number(value) => {
+                self.outputs
+                    .push(span.swap(output(value)));
+                Ok(())
+            }
+            _ => Err(Stop::Runtime),
+        }
+    }
+
+    /// Run a single instruction of the entry block, along with everything it calls
+    fn run(&mut self, instr: &Span<mir::Instr>) -> Result<(), Stop> {
+        let mut frames = Vec::new();
+
+        self.steps = self
+            .steps
+            .checked_sub(1)
+            .ok_or(Stop::Budget)?;
+
+        if let Some((_, callee)) = self.instr(instr, &[])? {
+            frames.push(callee);
+        }
+
+        while let Some(frame) = frames.last_mut() {
+            let Some(instr) = self.blocks[frame.block]
+                .instrs
+                .get(frame.instr)
+            else {
+                frames.pop();
+                continue;
+            };
+
+            self.steps = self
+                .steps
+                .checked_sub(1)
+                .ok_or(Stop::Budget)?;
+            frame.instr += 1;
+
+            let args = std::mem::take(&mut frame.args);
+            let result = self.instr(instr, &args);
+            let frame = frames.last_mut().unwrap();
+            frame.args = args;
+
+            match result? {
+                // Tail calls take over the caller's frame, so deep loops don't keep every frame around
+                Some((true, callee)) => *frame = callee,
+                Some((false, callee)) => frames.push(callee),
+                None => (),
+            }
+        }
+
+        Ok(())
+    }
+
+    fn instr(&mut self, instr: &Span<mir::Instr>, args: &[Value]) -> Result<Option<(bool, Frame)>, Stop> {
+        let (tail, function) = match &instr.data {
+            mir::Instr::Push(value) => {
+                let value = self.value(value, args)?;
+                self.stack.push(value);
+                return Ok(None);
+            }
+            mir::Instr::Command(command) => match command {
+                mir::Command::Call => (false, self.pop()),
+                mir::Command::OutputChar => {
+                    return self
+                        .output(instr.swap(()), Output::Char)
+                        .map(|()| None)
+                }
+                mir::Command::OutputNumber => {
+                    return self
+                        .output(instr.swap(()), Output::Number)
+                        .map(|()| None)
+                }
+                mir::Command::InputChar | mir::Command::InputNumber => return Err(Stop::Input),
+            },
+            mir::Instr::ImmediateCall(function) => (false, self.function(*function, args)),
+            mir::Instr::TailCall(function) => (true, self.function(*function, args)),
+            mir::Instr::ImmediateConditionalCall(value, x, y) | mir::Instr::ConditionalTailCall(value, x, y) => {
+                let function = match self.value(value, args)? {
+                    Value::Number(condition) if condition == ValueType::from(false) => *y,
+                    Value::Number(_) => *x,
+                    _ => return Err(Stop::Runtime),
+                };
+
+                (matches!(instr.data, mir::Instr::ConditionalTailCall(..)), self.function(function, args))
+            }
+        };
+
+        Ok(self
+            .call(function)?
+            .map(|frame| (tail, frame)))
+    }
+
+    /// Write a value back out as MIR, which is only possible if it doesn't capture any arguments
+    fn materialise(&self, value: Value) -> Option<mir::Value> {
+        match value {
+            Value::Number(value) => Some(mir::Value::Number(value)),
+            Value::BinOp(op) => Some(mir::Value::Function(mir::Function::BinOp(op))),
+            Value::TriOp(op) => Some(mir::Value::Function(mir::Function::TriOp(op))),
+            Value::Closure(closure) => {
+                let closure = &self.closures[closure];
+                closure
+                    .args
+                    .is_empty()
+                    .then_some(mir::Value::Function(mir::Function::Block(closure.block)))
+            }
+        }
+    }
+}
+
+impl OptimizationPass for PartialEvaluation {
+    fn name(&self) -> &'static str {
+        "Partial Evaluation"
+    }
+
+    fn is_optional(&self) -> bool {
+        true
+    }
+
+    fn run(&self, context: &mut OptimizationContext) -> bool {
+        let entry = &context.blocks()[0].instrs;
+        let mut machine = Machine::new(context.blocks(), context.options().evaluation_budget());
+        let mut checkpoint = machine.checkpoint(0);
+        let mut stop = None;
+
+        // Each instruction of the entry block is run in full or not at all, as there's no way to resume part way through a call
+        for (index, instr) in entry.iter().enumerate() {
+            if let Err(reason) = machine.run(instr) {
+                stop = Some((instr.swap(()), reason));
+                break;
+            }
+
+            if machine
+                .stack
+                .iter()
+                .all(|value| machine.materialise(*value).is_some())
+            {
+                checkpoint = machine.checkpoint(index + 1);
+            }
+        }
+
+        let Some(last) = checkpoint
+            .instr
+            .checked_sub(1)
+            .map(|index| entry[index].swap(()))
+        else {
+            if let Some((span, reason)) = stop {
+                context
+                    .remarks()
+                    .declined(span, || format!("could not evaluate: {}", reason.reason()));
+            }
+
+            return false;
+        };
+
+        let outputs = machine.outputs[..checkpoint.outputs]
+            .iter()
+            .flat_map(|output| {
+                let (value, command) = match output.data {
+                    Output::Char(value) => (value, mir::Command::OutputChar),
+                    Output::Number(value) => (value, mir::Command::OutputNumber),
+                };
+
+                [
+                    output.swap(mir::Instr::Push(mir::Value::Number(value))),
+                    output.swap(mir::Instr::Command(command)),
+                ]
+            });
+
+        let stack = checkpoint
+            .stack
+            .iter()
+            .map(|value| last.swap(mir::Instr::Push(machine.materialise(*value).unwrap())));
+
+        let instrs = outputs
+            .chain(stack)
+            .chain(
+                entry[checkpoint.instr..]
+                    .iter()
+                    .cloned(),
+            )
+            .collect();
+
+        let count = checkpoint.instr;
+        let remarks = context.remarks();
+
+        remarks.applied(last, || format!("evaluated the first {count} instructions of the program"));
+
+        if let Some((span, reason)) = stop {
+            remarks.declined(span, || format!("could not evaluate any further: {}", reason.reason()));
+        }
+
+        context.replace_instrs(0, instrs)
+    }
+}
@@ -0,0 +1,53 @@
+use catastrophic_mir::mir;
+
+use crate::optimizer::{context::OptimizationContext, remark::describe_function};
+
+use super::OptimizationPass;
+
+pub struct TailCalls;
+
+impl OptimizationPass for TailCalls {
+    fn name(&self) -> &'static str {
+        "Tail Call"
+    }
+
+    fn run(&self, context: &mut OptimizationContext) -> bool {
+        context.rewrite_blocks(|context| {
+            let mut instrs = context
+                .current_instrs()
+                .cloned()
+                .collect::<Vec<_>>();
+
+            // Only calls to blocks are worth replacing, as builtins never call anything themselves
+            if let Some(last) = instrs.last_mut() {
+                let blocks = context.blocks();
+
+                match last.data {
+                    mir::Instr::ImmediateCall(function @ mir::Function::Block(_)) => {
+                        last.data = mir::Instr::TailCall(function);
+                        context
+                            .remarks()
+                            .applied(last.swap(()), || {
+                                format!("made call to {} a tail call", describe_function(blocks, function))
+                            });
+                    }
+                    mir::Instr::ImmediateConditionalCall(ref value, x @ mir::Function::Block(_), y @ mir::Function::Block(_)) => {
+                        last.data = mir::Instr::ConditionalTailCall(value.clone(), x, y);
+                        context
+                            .remarks()
+                            .applied(last.swap(()), || {
+                                format!(
+                                    "made call to {} or {} a tail call",
+                                    describe_function(blocks, x),
+                                    describe_function(blocks, y)
+                                )
+                            });
+                    }
+                    _ => (),
+                }
+            }
+
+            instrs
+        })
+    }
+}
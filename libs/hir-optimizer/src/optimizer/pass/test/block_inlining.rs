@@ -0,0 +1,105 @@
+use catastrophic_mir::mir;
+
+use super::{
+    super::{
+        block_inlining::BlockInlining, constant_folding::ConstantFolding, immediate_calls::ImmediateCalls, immediate_operations::ImmediateOperations,
+    },
+    *,
+};
+
+const PASSES: [&dyn OptimizationPass; 4] = [&ImmediateCalls, &ImmediateOperations, &ConstantFolding, &BlockInlining];
+
+fn inline(input: &str) -> Vec<mir::Instr> {
+    instrs(&optimize(input, &PASSES)[0])
+}
+
+#[test]
+fn inline_constant_block() {
+    assert_eq!(inline("true: a -> { 1 1 = () }\n5 true ()"), [mir::Instr::Push(number(1))]);
+}
+
+#[test]
+fn inline_remaps_args() {
+    assert_eq!(
+        inline("inc: x -> { x 1 + () }\n5 inc ()"),
+        [mir::Instr::Push(bin_op(mir::BinOp::Plus, number(1), number(5)))]
+    );
+}
+
+#[test]
+fn inline_keeps_captured_args() {
+    let blocks = optimize("outer: y -> {\n    add: x -> { x y + () }\n    3 add ()\n}", &PASSES);
+
+    assert_eq!(
+        instrs(&blocks[labelled(&blocks, "outer")]),
+        [mir::Instr::Push(bin_op(mir::BinOp::Plus, mir::Value::Arg(0), number(3)))]
+    );
+}
+
+#[test]
+fn no_inline_recursive_block() {
+    let blocks = optimize("loop: { 1 . loop () }\nloop ()", &PASSES);
+    let index = labelled(&blocks, "loop");
+
+    assert_eq!(instrs(&blocks[0]), [mir::Instr::ImmediateCall(mir::Function::Block(index))]);
+}
+
+#[test]
+fn no_inline_above_threshold() {
+    let blocks = optimize_with(
+        "inc: x -> { x 1 + () }\n5 inc ()",
+        &PASSES,
+        &Options::no_passes().with_inline_threshold(0),
+    );
+    let index = labelled(&blocks, "inc");
+
+    assert_eq!(
+        instrs(&blocks[0]),
+        [mir::Instr::Push(number(5)), mir::Instr::ImmediateCall(mir::Function::Block(index))]
+    );
+}
+
+#[test]
+fn no_inline_unknown_args() {
+    let blocks = optimize("inc: x -> { x 1 + () }\n~ inc ()", &PASSES);
+    let index = labelled(&blocks, "inc");
+
+    assert_eq!(
+        instrs(&blocks[0]),
+        [
+            mir::Instr::Command(mir::Command::InputChar),
+            mir::Instr::ImmediateCall(mir::Function::Block(index))
+        ]
+    );
+}
+
+#[test]
+fn no_inline_impure_args() {
+    let blocks = optimize("double: x -> { x x + () }\n1 6 ! () double ()", &PASSES);
+
+    assert!(matches!(
+        instrs(&blocks[0])[..],
+        [mir::Instr::Push(_), mir::Instr::ImmediateCall(mir::Function::Block(_))]
+    ));
+}
+
+#[test]
+fn no_inline_args_which_could_fail() {
+    let blocks = optimize("ignore: x -> { 1 . }\n0 1 / () ignore ()", &PASSES);
+
+    assert!(matches!(
+        instrs(&blocks[0])[..],
+        [mir::Instr::Push(_), mir::Instr::ImmediateCall(mir::Function::Block(_))]
+    ));
+}
+
+#[test]
+fn no_inline_capturing_blocks() {
+    let blocks = optimize("print: x -> { { x . } () }\n5 print ()", &PASSES);
+    let index = labelled(&blocks, "print");
+
+    assert_eq!(
+        instrs(&blocks[0]),
+        [mir::Instr::Push(number(5)), mir::Instr::ImmediateCall(mir::Function::Block(index))]
+    );
+}
@@ -0,0 +1,127 @@
+use catastrophic_mir::mir;
+
+use super::{
+    super::{
+        block_specialisation::BlockSpecialisation, constant_folding::ConstantFolding, immediate_calls::ImmediateCalls,
+        immediate_operations::ImmediateOperations,
+    },
+    *,
+};
+
+const PASSES: [&dyn OptimizationPass; 4] = [&ImmediateCalls, &ImmediateOperations, &ConstantFolding, &BlockSpecialisation];
+
+fn block_call(index: usize) -> mir::Instr {
+    mir::Instr::ImmediateCall(mir::Function::Block(index))
+}
+
+fn called(block: &mir::Block) -> usize {
+    match block
+        .instrs
+        .last()
+        .map(|instr| &instr.data)
+    {
+        Some(mir::Instr::ImmediateCall(mir::Function::Block(index))) => *index,
+        instr => panic!("expected a call, found {instr:?}"),
+    }
+}
+
+#[test]
+fn specialise_constant_args() {
+    let blocks = optimize("f: x -> y -> { x y - () . }\n3 5 f ()", &PASSES);
+    let specialised = called(&blocks[0]);
+
+    assert_eq!(instrs(&blocks[0]), [block_call(specialised)]);
+    assert_eq!(blocks[specialised].args, 0);
+    assert_eq!(
+        instrs(&blocks[specialised]),
+        [mir::Instr::Push(number(2)), mir::Instr::Command(mir::Command::OutputNumber)]
+    );
+}
+
+#[test]
+fn specialise_leading_args_only() {
+    let blocks = optimize("f: x -> y -> { x y - () . }\ng: x -> { x 5 f () }\n1 g ()", &PASSES);
+    let g = labelled(&blocks, "g");
+    let specialised = called(&blocks[g]);
+
+    assert_eq!(instrs(&blocks[g]), [mir::Instr::Push(mir::Value::Arg(0)), block_call(specialised)]);
+    assert_eq!(blocks[specialised].args, 1);
+    assert_eq!(
+        instrs(&blocks[specialised]),
+        [
+            mir::Instr::Push(bin_op(mir::BinOp::Minus, number(5), mir::Value::Arg(0))),
+            mir::Instr::Command(mir::Command::OutputNumber)
+        ]
+    );
+}
+
+#[test]
+fn reuse_specialisation() {
+    let blocks = optimize("f: x -> { x . }\n3 f () 3 f ()", &PASSES);
+    let specialised = called(&blocks[0]);
+
+    assert_eq!(instrs(&blocks[0]), [block_call(specialised), block_call(specialised)]);
+}
+
+#[test]
+fn specialise_recursive_block() {
+    let options = Options::no_passes();
+    let mut context = OptimizationContext::new(lower("loop: x -> { x . x loop () }\n1 loop ()"), &options);
+
+    for pass in PASSES.iter().chain(&PASSES) {
+        pass.run(&mut context);
+    }
+
+    let blocks = context.into_blocks();
+    let specialised = called(&blocks[0]);
+
+    // The copy calls itself rather than going back to the original block
+    assert_eq!(called(&blocks[specialised]), specialised);
+    assert_eq!(
+        instrs(&blocks[specialised]),
+        [
+            mir::Instr::Push(number(1)),
+            mir::Instr::Command(mir::Command::OutputNumber),
+            block_call(specialised)
+        ]
+    );
+}
+
+#[test]
+fn specialise_nested_blocks() {
+    let blocks = optimize("f: x -> { g: { x . }\ng () }\n1 f ()", &PASSES);
+    let specialised = called(&blocks[0]);
+    let nested = called(&blocks[specialised]);
+
+    // The nested block captured the argument, so gets a copy of its own
+    assert_ne!(nested, labelled(&blocks, "g"));
+    assert_eq!(blocks[nested].offset, 0);
+    assert_eq!(
+        instrs(&blocks[nested]),
+        [mir::Instr::Push(number(1)), mir::Instr::Command(mir::Command::OutputNumber)]
+    );
+}
+
+#[test]
+fn no_specialise_unknown_args() {
+    let blocks = optimize("f: x -> { x . }\n~ f ()", &PASSES);
+    let f = labelled(&blocks, "f");
+
+    assert_eq!(instrs(&blocks[0]), [mir::Instr::Command(mir::Command::InputChar), block_call(f)]);
+}
+
+#[test]
+fn no_specialise_capturing_block_args() {
+    let blocks = optimize(
+        "f: g -> { g () }\nh: x -> { k: { x . }\nk f () }\n1 h ()",
+        &[&ImmediateCalls, &BlockSpecialisation],
+    );
+    let h = labelled(&blocks, "h");
+    let k = labelled(&blocks, "k");
+
+    // `k` captures the arguments of `h`, so must be pushed there rather than within `f`
+    assert_eq!(
+        &instrs(&blocks[h])[..1],
+        [mir::Instr::Push(mir::Value::Function(mir::Function::Block(k)))]
+    );
+}
@@ -0,0 +1,89 @@
+use catastrophic_mir::mir;
+
+use super::{
+    super::{
+        branch_elimination::BranchElimination, constant_folding::ConstantFolding, immediate_calls::ImmediateCalls,
+        immediate_conditional_calls::ImmediateConditionalCalls, immediate_operations::ImmediateOperations,
+    },
+    *,
+};
+
+const PASSES: [&dyn OptimizationPass; 5] = [
+    &ImmediateCalls,
+    &ImmediateOperations,
+    &ConstantFolding,
+    &ImmediateConditionalCalls,
+    &BranchElimination,
+];
+
+fn called_block(blocks: &[mir::Block], instr: &mir::Instr) -> Vec<mir::Instr> {
+    match instr {
+        mir::Instr::ImmediateCall(mir::Function::Block(index)) => instrs(&blocks[*index]),
+        instr => panic!("expected an immediate block call, found {instr:?}"),
+    }
+}
+
+#[test]
+fn eliminate_constant_conditional_call() {
+    let blocks = optimize("{ 1 . } { 2 . } 1 1 = () ? () ()", &PASSES);
+    let top_level = instrs(&blocks[0]);
+
+    assert_eq!(top_level.len(), 1);
+    assert_eq!(called_block(&blocks, &top_level[0])[0], mir::Instr::Push(number(2)));
+}
+
+#[test]
+fn eliminate_false_conditional_call() {
+    let blocks = optimize("{ 1 . } { 2 . } 1 0 = () ? () ()", &PASSES);
+    let top_level = instrs(&blocks[0]);
+
+    assert_eq!(top_level.len(), 1);
+    assert_eq!(called_block(&blocks, &top_level[0])[0], mir::Instr::Push(number(1)));
+}
+
+#[test]
+fn keep_unknown_conditional_call() {
+    let blocks = optimize("x -> { {} {} x ? () () }", &PASSES);
+
+    assert!(matches!(
+        instrs(&blocks[1])[..],
+        [mir::Instr::ImmediateConditionalCall(mir::Value::Arg(0), _, _)]
+    ));
+}
+
+#[test]
+fn eliminate_constant_if_then_else() {
+    let blocks = optimize("x -> { x 5 1 ? () }\ny -> { y 5 0 ? () }", &PASSES);
+
+    assert_eq!(instrs(&blocks[1]), [mir::Instr::Push(number(5))]);
+    assert_eq!(instrs(&blocks[2]), [mir::Instr::Push(mir::Value::Arg(0))]);
+}
+
+#[test]
+fn keep_if_then_else_whose_other_branch_could_fail() {
+    let blocks = optimize("x -> { 0 1 / () x 1 ? () }", &PASSES);
+
+    assert!(matches!(
+        instrs(&blocks[1])[..],
+        [mir::Instr::Push(mir::Value::ImmediateTriOp(mir::TriOp::IfThenElse, ..))]
+    ));
+}
+
+#[test]
+fn eliminate_nested_if_then_else() {
+    let input = vec![mir::Instr::Push(bin_op(
+        mir::BinOp::Plus,
+        mir::Value::ImmediateTriOp(
+            mir::TriOp::IfThenElse,
+            Box::new(number(0)),
+            Box::new(mir::Value::Arg(0)),
+            Box::new(mir::Value::Arg(1)),
+        ),
+        number(1),
+    ))];
+
+    assert_eq!(
+        run(&[&BranchElimination], input),
+        [mir::Instr::Push(bin_op(mir::BinOp::Plus, mir::Value::Arg(1), number(1)))]
+    );
+}
@@ -0,0 +1,87 @@
+use catastrophic_mir::mir;
+
+use super::{
+    super::{constant_folding::ConstantFolding, immediate_calls::ImmediateCalls, immediate_operations::ImmediateOperations},
+    *,
+};
+
+const PASSES: [&dyn OptimizationPass; 3] = [&ImmediateCalls, &ImmediateOperations, &ConstantFolding];
+
+fn fold(input: &str) -> Vec<mir::Instr> {
+    instrs(&optimize(input, &PASSES)[0])
+}
+
+#[test]
+fn fold_arithmetic() {
+    assert_eq!(fold("2 3 + ()"), [mir::Instr::Push(number(5))]);
+    assert_eq!(fold("5 3 - ()"), [mir::Instr::Push(number(-2))]);
+    assert_eq!(fold("4 3 * ()"), [mir::Instr::Push(number(12))]);
+    assert_eq!(fold("3 12 / ()"), [mir::Instr::Push(number(4))]);
+}
+
+#[test]
+fn fold_comparisons() {
+    assert_eq!(fold("1 0 = ()"), [mir::Instr::Push(number(0))]);
+    assert_eq!(fold("1 1 = ()"), [mir::Instr::Push(number(1))]);
+    assert_eq!(fold("3 5 > ()"), [mir::Instr::Push(number(1))]);
+    assert_eq!(fold("3 5 < ()"), [mir::Instr::Push(number(0))]);
+}
+
+#[test]
+fn fold_nested_operations() {
+    assert_eq!(fold("1 2 + () 3 * ()"), [mir::Instr::Push(number(9))]);
+}
+
+#[test]
+fn fold_if_then_else() {
+    assert_eq!(fold("2 1 0 ? ()"), [mir::Instr::Push(number(2))]);
+    assert_eq!(fold("2 1 7 ? ()"), [mir::Instr::Push(number(1))]);
+}
+
+#[test]
+fn fold_partially_constant_tree() {
+    let blocks = optimize("x -> { x 1 2 + () + () }", &PASSES);
+
+    assert_eq!(
+        instrs(&blocks[1]),
+        [mir::Instr::Push(bin_op(mir::BinOp::Plus, number(3), mir::Value::Arg(0)))]
+    );
+}
+
+#[test]
+fn fold_conditional_call_condition() {
+    let input = vec![mir::Instr::ImmediateConditionalCall(
+        bin_op(mir::BinOp::Equals, number(1), number(1)),
+        mir::Function::Block(1),
+        mir::Function::Block(2),
+    )];
+
+    assert_eq!(
+        run(&[&ConstantFolding], input),
+        [mir::Instr::ImmediateConditionalCall(
+            number(1),
+            mir::Function::Block(1),
+            mir::Function::Block(2)
+        )]
+    );
+}
+
+#[test]
+fn no_fold_division_by_zero() {
+    assert_eq!(fold("0 1 / ()"), [mir::Instr::Push(bin_op(mir::BinOp::Divide, number(1), number(0)))]);
+}
+
+#[test]
+fn no_fold_overflow() {
+    let input = vec![
+        mir::Instr::Push(bin_op(mir::BinOp::Plus, number(i64::MAX), number(1))),
+        mir::Instr::Push(bin_op(mir::BinOp::Divide, number(i64::MIN), number(-1))),
+    ];
+
+    assert_eq!(run(&[&ConstantFolding], input.clone()), input);
+}
+
+#[test]
+fn no_fold_random() {
+    assert_eq!(fold("1 6 ! ()"), [mir::Instr::Push(bin_op(mir::BinOp::Random, number(6), number(1)))]);
+}
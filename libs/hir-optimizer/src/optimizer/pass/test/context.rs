@@ -0,0 +1,117 @@
+use std::collections::BTreeSet;
+
+use catastrophic_core::profiling::TimeKeeper;
+use catastrophic_mir::mir;
+
+use super::{
+    super::{constant_folding::ConstantFolding, immediate_calls::ImmediateCalls, immediate_operations::ImmediateOperations},
+    *,
+};
+use crate::optimizer::Optimizer;
+
+fn call(index: usize) -> mir::Instr {
+    mir::Instr::ImmediateCall(mir::Function::Block(index))
+}
+
+#[test]
+fn call_graph_follows_references() {
+    let blocks = lower("a: { b () }\nb: { {} }\nc: x -> { x c () }\na ()");
+    let options = Options::no_passes();
+    let context = OptimizationContext::new(blocks, &options);
+    let call_graph = context.call_graph();
+    let [a, b, c] = ["a", "b", "c"].map(|label| labelled(context.blocks(), label));
+
+    assert!(call_graph.references(a).contains(&b));
+    assert!(call_graph.referrers(b).contains(&a));
+    assert!(call_graph
+        .reachable(0)
+        .is_superset(&BTreeSet::from([a, b])));
+    assert!(!call_graph.is_recursive(a));
+    assert!(call_graph.is_recursive(c));
+}
+
+#[test]
+fn call_graph_updates_match_a_new_graph() {
+    let blocks = lower("a: { b () }\nb: { {} }\nc: x -> { x c () }\na ()");
+    let options = Options::no_passes();
+    let mut context = OptimizationContext::new(blocks, &options);
+    let mut call_graph = context.call_graph();
+    let [a, b, c] = ["a", "b", "c"].map(|label| labelled(context.blocks(), label));
+
+    // The new block refers to one added after it, as specialised copies of nested blocks do
+    let next = context.blocks().len() + 1;
+    let added = block(vec![call(c), call(next)]);
+    call_graph.add_block(&added);
+    context.add_block(added);
+
+    let added = block(vec![mir::Instr::Push(mir::Value::Function(mir::Function::Block(a)))]);
+    call_graph.add_block(&added);
+    context.add_block(added);
+
+    context.replace_instrs(a, vec![span(call(c))]);
+    call_graph.update_block(a, &context.blocks()[a]);
+
+    let expected = context.call_graph();
+
+    for index in 0..context.blocks().len() {
+        assert_eq!(call_graph.references(index), expected.references(index));
+        assert_eq!(call_graph.referrers(index), expected.referrers(index));
+        assert!(!expected.is_first_class(index) || call_graph.is_first_class(index));
+    }
+
+    assert!(!call_graph.referrers(b).contains(&a));
+}
+
+#[test]
+fn passes_report_changes() {
+    let options = Options::no_passes();
+    let mut context = OptimizationContext::new(lower("1 2 + ()"), &options);
+
+    assert!(!ConstantFolding.run(&mut context));
+    assert!(ImmediateCalls.run(&mut context));
+    assert!(ImmediateOperations.run(&mut context));
+    assert!(ConstantFolding.run(&mut context));
+    assert!(!ConstantFolding.run(&mut context));
+}
+
+#[test]
+fn add_block_returns_index() {
+    let options = Options::no_passes();
+    let mut context = OptimizationContext::new(vec![block(Vec::new())], &options);
+
+    assert_eq!(context.add_block(block(vec![mir::Instr::Push(number(1))])), 1);
+    assert_eq!(context.blocks().len(), 2);
+}
+
+#[test]
+fn remove_blocks_renumbers_references() {
+    let mut inner = block(vec![mir::Instr::Push(number(3))]);
+    inner.source.parent = Some(1);
+
+    let blocks = vec![
+        block(vec![call(3), mir::Instr::Push(mir::Value::Function(mir::Function::Block(3)))]),
+        block(Vec::new()),
+        block(Vec::new()),
+        inner,
+    ];
+
+    let options = Options::no_passes();
+    let mut context = OptimizationContext::new(blocks, &options);
+    context.remove_blocks(&BTreeSet::from([1, 2]));
+
+    assert_eq!(context.blocks().len(), 2);
+    assert_eq!(
+        instrs(&context.blocks()[0]),
+        [call(1), mir::Instr::Push(mir::Value::Function(mir::Function::Block(1)))]
+    );
+    assert_eq!(context.blocks()[1].source.parent, None);
+}
+
+#[test]
+fn optimizer_repeats_passes_until_fixpoint() {
+    let mut time_keeper = TimeKeeper::new(&"Test");
+    let mut time_scope = time_keeper.scope(&"Optimization");
+    let blocks = Optimizer::optimize_mir(&Options::all_passes(), lower("inc: x -> { x 1 + () }\n5 inc ()"), &mut time_scope).unwrap();
+
+    assert_eq!(instrs(&blocks[0]), [mir::Instr::Push(number(6))]);
+}
@@ -0,0 +1,49 @@
+use catastrophic_mir::mir;
+
+use super::{
+    super::{dead_block_elimination::DeadBlockElimination, immediate_calls::ImmediateCalls},
+    *,
+};
+
+const PASSES: [&dyn OptimizationPass; 2] = [&ImmediateCalls, &DeadBlockElimination];
+
+fn call(index: usize) -> mir::Instr {
+    mir::Instr::ImmediateCall(mir::Function::Block(index))
+}
+
+#[test]
+fn remove_unreferenced_blocks() {
+    let blocks = optimize("unused: { 1 }\nused: { 2 }\nused ()", &PASSES);
+
+    assert_eq!(blocks.len(), 2);
+    assert_eq!(instrs(&blocks[0]), [call(labelled(&blocks, "used"))]);
+}
+
+#[test]
+fn renumber_remaining_blocks() {
+    let blocks = optimize("unused: { 0 }\na: { 1 }\nb: { a () }\nb ()", &PASSES);
+    let [a, b] = ["a", "b"].map(|label| labelled(&blocks, label));
+
+    assert_eq!(blocks.len(), 3);
+    assert_eq!(instrs(&blocks[0]), [call(b)]);
+    assert_eq!(instrs(&blocks[b]), [call(a)]);
+    assert_eq!(instrs(&blocks[a]), [mir::Instr::Push(number(1))]);
+}
+
+#[test]
+fn mark_directly_called_blocks() {
+    let blocks = optimize("direct: { 1 }\nvalue: { 2 }\ndirect () value", &PASSES);
+
+    assert!(!blocks[labelled(&blocks, "direct")].first_class);
+    assert!(blocks[labelled(&blocks, "value")].first_class);
+}
+
+#[test]
+fn keep_blocks_reachable_through_values() {
+    let blocks = optimize("inner: { 1 }\nouter: { inner }\nouter ()", &PASSES);
+    let inner = labelled(&blocks, "inner");
+
+    assert_eq!(blocks.len(), 3);
+    assert!(blocks[inner].first_class);
+    assert!(!blocks[labelled(&blocks, "outer")].first_class);
+}
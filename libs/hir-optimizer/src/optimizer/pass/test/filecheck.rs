@@ -0,0 +1,144 @@
+//! Runs the optimizer over programs written in textual MIR, checking the output against the
+//! directives alongside them, in the style of LLVM's `FileCheck`
+//!
+//! Lines starting with `//` are directives or comments, and everything else is the input program:
+//!
+//! - `// RUN: <passes>` gives the pipeline to run, in the same form as `--passes`
+//! - `// CHECK: <text>` matches the first line after the previous match which contains the text
+//! - `// CHECK-NEXT: <text>` matches the line directly after the previous match
+//! - `// CHECK-NOT: <text>` fails if the text appears between the matches either side of it
+
+use catastrophic_core::profiling::TimeKeeper;
+use catastrophic_mir::mir;
+
+use crate::optimizer::{Optimizer, Options};
+
+enum Check<'a> {
+    Contains(&'a str),
+    Next(&'a str),
+    Not(&'a str),
+}
+
+struct TestFile<'a> {
+    passes: &'a str,
+    checks: Vec<Check<'a>>,
+    input: String,
+}
+
+impl<'a> TestFile<'a> {
+    fn parse(file: &'a str) -> Self {
+        let mut passes = None;
+        let mut checks = Vec::new();
+        let mut input = String::new();
+
+        for line in file.lines() {
+            let Some(directive) = line.trim().strip_prefix("//") else {
+                input.push_str(line);
+                input.push('\n');
+                continue;
+            };
+
+            let directive = directive.trim();
+
+            if let Some(spec) = directive.strip_prefix("RUN:") {
+                passes = Some(spec.trim());
+            } else if let Some(text) = directive.strip_prefix("CHECK:") {
+                checks.push(Check::Contains(text.trim()));
+            } else if let Some(text) = directive.strip_prefix("CHECK-NEXT:") {
+                checks.push(Check::Next(text.trim()));
+            } else if let Some(text) = directive.strip_prefix("CHECK-NOT:") {
+                checks.push(Check::Not(text.trim()));
+            }
+        }
+
+        Self {
+            passes: passes.expect("missing `// RUN:` directive"),
+            checks,
+            input,
+        }
+    }
+}
+
+fn check_not(output: &[&str], range: std::ops::Range<usize>, nots: &mut Vec<&str>) {
+    for text in nots.drain(..) {
+        if let Some(line) = output[range.clone()]
+            .iter()
+            .find(|line| line.contains(text))
+        {
+            panic!("`CHECK-NOT: {text}` found `{line}` in output:\n{}", output.join("\n"));
+        }
+    }
+}
+
+fn filecheck(file: &str) {
+    let test_file = TestFile::parse(file);
+    let options = Options::from_spec(test_file.passes).unwrap();
+
+    let blocks = mir::parse(&test_file.input).unwrap_or_else(|error| panic!("invalid input MIR: {error}"));
+    mir::verify(&blocks).unwrap_or_else(|error| panic!("malformed input MIR: {error}"));
+
+    let mut time_keeper = TimeKeeper::new(&"FileCheck");
+    let blocks = Optimizer::optimize_mir(&options, blocks, &mut time_keeper.scope(&"Optimization")).unwrap();
+
+    let output = mir::print(&blocks);
+    let output = output
+        .lines()
+        .map(str::trim)
+        .collect::<Vec<_>>();
+
+    let mut next = 0;
+    let mut nots = Vec::new();
+
+    for check in test_file.checks {
+        match check {
+            Check::Contains(text) => {
+                let Some(found) = (next..output.len()).find(|index| output[*index].contains(text)) else {
+                    panic!("`CHECK: {text}` not found in output:\n{}", output.join("\n"));
+                };
+
+                check_not(&output, next..found, &mut nots);
+                next = found + 1;
+            }
+            Check::Next(text) => {
+                if !output
+                    .get(next)
+                    .is_some_and(|line| line.contains(text))
+                {
+                    panic!("`CHECK-NEXT: {text}` not found on line {} of output:\n{}", next + 1, output.join("\n"));
+                }
+
+                next += 1;
+            }
+            Check::Not(text) => nots.push(text),
+        }
+    }
+
+    check_not(&output, next..output.len(), &mut nots);
+}
+
+macro_rules! filecheck_tests {
+    ($($name:ident),* $(,)?) => {
+        $(
+            #[test]
+            fn $name() {
+                filecheck(include_str!(concat!("filecheck/", stringify!($name), ".mir")));
+            }
+        )*
+    };
+}
+
+filecheck_tests!(
+    constant_folding,
+    branch_elimination,
+    block_inlining,
+    immediate_calls,
+    tail_calls,
+    dead_block_elimination,
+    block_specialisation,
+);
+
+#[test]
+#[should_panic(expected = "`CHECK-NOT: Push[2]` found `Push[2]`")]
+fn check_not_fails() {
+    filecheck("// RUN:\nmain(offset: 0, args: 0) {\n    Push[1]\n    Push[2]\n}\n// CHECK: main\n// CHECK-NOT: Push[2]\n// CHECK: }");
+}
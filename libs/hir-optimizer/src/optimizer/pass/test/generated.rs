@@ -0,0 +1,46 @@
+use catastrophic_core::profiling::TimeKeeper;
+use catastrophic_test_support::generator::{Config, Program};
+
+use crate::optimizer::Optimizer;
+
+use super::*;
+
+/// Run the given passes followed by partial evaluation, which leaves only the output of a program which is fully evaluated
+fn evaluate(blocks: Vec<mir::Block>, options: Options) -> Vec<mir::Instr> {
+    let options = options
+        .with_pass("partial-evaluation")
+        .unwrap();
+
+    let mut time_keeper = TimeKeeper::new(&"Test");
+    let blocks = Optimizer::optimize_mir(&options, blocks, &mut time_keeper.scope(&"Optimization")).unwrap();
+    instrs(&blocks[0])
+}
+
+fn only_output(instrs: &[mir::Instr]) -> bool {
+    instrs.chunks(2).all(|pair| {
+        matches!(
+            pair,
+            [
+                mir::Instr::Push(mir::Value::Number(_)),
+                mir::Instr::Command(mir::Command::OutputChar | mir::Command::OutputNumber)
+            ]
+        )
+    })
+}
+
+#[test]
+fn optimizing_generated_programs_keeps_their_output() {
+    let config = Config::default();
+
+    for seed in 0..200 {
+        let program = Program::generate(seed, &config);
+        let blocks = lower(&program.to_string());
+
+        let expected = evaluate(blocks.clone(), Options::no_passes());
+        let optimized = evaluate(blocks, Options::all_passes());
+
+        // Generated programs always finish without input, so should be evaluated all the way through
+        assert!(only_output(&expected), "seed {seed} was not fully evaluated:\n{program}");
+        assert_eq!(optimized, expected, "seed {seed}:\n{program}");
+    }
+}
@@ -0,0 +1,108 @@
+#![cfg(test)]
+
+use catastrophic_analyser::analyser::Analyser;
+use catastrophic_core::span::{Location, Span};
+use catastrophic_mir::mir;
+use catastrophic_parser::parser::Parser;
+
+use crate::optimizer::{context::OptimizationContext, convert, Options};
+
+use super::OptimizationPass;
+
+mod block_inlining;
+mod block_specialisation;
+mod branch_elimination;
+mod constant_folding;
+mod context;
+mod dead_block_elimination;
+mod filecheck;
+mod generated;
+mod options;
+mod partial_evaluation;
+mod remarks;
+mod tail_calls;
+mod text_format;
+mod verify;
+
+fn span<T>(data: T) -> Span<T> {
+    Span::new(Location::default(), Location::default(), data)
+}
+
+fn block(instrs: Vec<mir::Instr>) -> mir::Block {
+    mir::Block {
+        offset: 0,
+        args: 0,
+        instrs: instrs.into_iter().map(span).collect(),
+        name: "test".into(),
+        source: mir::BlockSource::default(),
+        first_class: true,
+    }
+}
+
+fn labelled(blocks: &[mir::Block], label: &str) -> usize {
+    blocks
+        .iter()
+        .position(|block| block.source.label.as_deref() == Some(label))
+        .unwrap()
+}
+
+fn instrs(block: &mir::Block) -> Vec<mir::Instr> {
+    block
+        .instrs
+        .iter()
+        .map(|instr| instr.data.clone())
+        .collect()
+}
+
+/// Run each of the given passes once, in order
+fn run_passes(blocks: Vec<mir::Block>, passes: &[&dyn OptimizationPass], options: &Options) -> Vec<mir::Block> {
+    let mut context = OptimizationContext::new(blocks, options);
+
+    for pass in passes {
+        pass.run(&mut context);
+    }
+
+    context.into_blocks()
+}
+
+/// Run the given passes over a single block made up of the given instructions
+///
+/// The block may refer to arguments and blocks which don't exist, so isn't checked to be well formed
+fn run(passes: &[&dyn OptimizationPass], input: Vec<mir::Instr>) -> Vec<mir::Instr> {
+    let blocks = run_passes(vec![block(input)], passes, &Options::no_passes());
+    instrs(&blocks[0])
+}
+
+fn lower(input: &str) -> Vec<mir::Block> {
+    let ast = Parser::with_str(input)
+        .parse()
+        .unwrap()
+        .ast;
+
+    convert::convert_blocks(Analyser::analyse_ast(ast).unwrap())
+}
+
+/// Lower the source to MIR and run the given passes over every block
+fn optimize(input: &str, passes: &[&dyn OptimizationPass]) -> Vec<mir::Block> {
+    optimize_with(input, passes, &Options::no_passes())
+}
+
+/// Lower the source to MIR and run the given passes, checking the program is still well formed after each of them
+fn optimize_with(input: &str, passes: &[&dyn OptimizationPass], options: &Options) -> Vec<mir::Block> {
+    let mut context = OptimizationContext::new(lower(input), options);
+
+    for pass in passes {
+        pass.run(&mut context);
+        context.verify(pass.name()).unwrap();
+    }
+
+    context.into_blocks()
+}
+
+fn number(value: i64) -> mir::Value {
+    mir::Value::Number(value)
+}
+
+fn bin_op(op: mir::BinOp, x: mir::Value, y: mir::Value) -> mir::Value {
+    mir::Value::ImmediateBinOp(op, Box::new(x), Box::new(y))
+}
@@ -0,0 +1,121 @@
+use catastrophic_core::profiling::TimeKeeper;
+use catastrophic_mir::mir;
+
+use super::{
+    super::{immediate_calls::ImmediateCalls, immediate_operations::ImmediateOperations},
+    *,
+};
+use crate::optimizer::{statistics::Statistics, Optimizer, UnknownPass};
+
+fn steps(options: &Options) -> Vec<(String, bool)> {
+    options
+        .steps
+        .iter()
+        .map(|step| (step.pass.id(), step.repeat))
+        .collect()
+}
+
+#[test]
+fn parse_pass_spec() {
+    let options = Options::from_spec("immediate-call, Immediate Operation*,immediate-call").unwrap();
+
+    assert_eq!(
+        steps(&options),
+        [
+            ("immediate-call".to_owned(), false),
+            ("immediate-operation".to_owned(), true),
+            ("immediate-call".to_owned(), false)
+        ]
+    );
+}
+
+#[test]
+fn reject_unknown_pass() {
+    assert_eq!(
+        Options::from_spec("immediate-call,loop-unrolling*").err(),
+        Some(UnknownPass("loop-unrolling".to_owned()))
+    );
+    assert!(Options::all_passes()
+        .without_pass("loop-unrolling")
+        .is_err());
+}
+
+#[test]
+fn skip_several_passes() {
+    let options = Options::from_spec("immediate-call,constant-folding,immediate-call,block-inlining")
+        .unwrap()
+        .without_pass("immediate-call")
+        .unwrap()
+        .without_pass("Block Inlining")
+        .unwrap();
+
+    assert_eq!(steps(&options), [("constant-folding".to_owned(), false)]);
+}
+
+#[test]
+fn only_repeat_starred_passes() {
+    let optimize = |spec| {
+        let mut time_keeper = TimeKeeper::new(&"Test");
+        let input = lower("c: { 1 }\nb: { c () }\na: { b () }\na ()");
+        Optimizer::optimize_mir(&Options::from_spec(spec).unwrap(), input, &mut time_keeper.scope(&"Optimization")).unwrap()
+    };
+
+    // Each run of inlining only reaches one call further into the entry block
+    let once = optimize("immediate-call,block-inlining");
+    let repeated = optimize("immediate-call,block-inlining*");
+
+    assert!(matches!(instrs(&once[0])[..], [mir::Instr::ImmediateCall(mir::Function::Block(_))]));
+    assert_eq!(instrs(&repeated[0]), [mir::Instr::Push(number(1))]);
+}
+
+#[test]
+fn compare_rewritten_instructions() {
+    let before = [1, 2, 3, 4].map(|value| span(mir::Instr::Push(number(value))));
+    let after = [1, 5, 4].map(|value| span(mir::Instr::Push(number(value))));
+
+    assert_eq!(
+        Statistics::compare(&before, &after),
+        Statistics {
+            removed: 1,
+            rewritten: 1,
+            added: 0
+        }
+    );
+    assert!(Statistics::compare(&before, &before).is_empty());
+}
+
+#[test]
+fn passes_record_statistics() {
+    let options = Options::no_passes();
+    let mut context = OptimizationContext::new(lower("1 2 + ()"), &options);
+
+    ImmediateCalls.run(&mut context);
+    context.take_statistics();
+    ImmediateOperations.run(&mut context);
+
+    assert_eq!(
+        context.take_statistics(),
+        Statistics {
+            removed: 2,
+            rewritten: 1,
+            added: 0
+        }
+    );
+    assert!(context.take_statistics().is_empty());
+}
+
+#[test]
+fn optional_passes_are_added_explicitly() {
+    let ids = |options: &Options| {
+        steps(options)
+            .into_iter()
+            .map(|(id, _)| id)
+            .collect::<Vec<_>>()
+    };
+
+    assert!(!ids(&Options::all_passes()).contains(&"partial-evaluation".to_owned()));
+    assert!(ids(&Options::all_passes()
+        .with_pass("partial-evaluation")
+        .unwrap())
+    .contains(&"partial-evaluation".to_owned()));
+}
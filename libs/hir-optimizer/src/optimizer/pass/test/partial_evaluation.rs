@@ -0,0 +1,76 @@
+use catastrophic_mir::mir;
+
+use super::{super::partial_evaluation::PartialEvaluation, *};
+
+fn evaluate(input: &str, budget: usize) -> Vec<mir::Instr> {
+    let options = Options::no_passes().with_evaluation_budget(budget);
+    instrs(&optimize_with(input, &[&PartialEvaluation], &options)[0])
+}
+
+fn output_number(value: i64) -> [mir::Instr; 2] {
+    [mir::Instr::Push(number(value)), mir::Instr::Command(mir::Command::OutputNumber)]
+}
+
+#[test]
+fn evaluate_static_program() {
+    assert_eq!(
+        evaluate("double: x -> { x x + () }\n3 double () . 7 double () .", 1000),
+        [output_number(6), output_number(14)].concat()
+    );
+}
+
+#[test]
+fn evaluate_loop_with_tail_calls() {
+    let output = evaluate("count: n -> { n . { 1 n - () count () } {} n 0 = () ? () () }\n3 count ()", 1000);
+
+    assert_eq!(output, [output_number(3), output_number(2), output_number(1), output_number(0)].concat());
+}
+
+#[test]
+fn keep_final_stack() {
+    // Compiled programs exit with the value left on top of the stack
+    assert_eq!(
+        evaluate("4 . 1 2 + ()", 1000),
+        [output_number(4).to_vec(), vec![mir::Instr::Push(number(3))]].concat()
+    );
+}
+
+#[test]
+fn stop_at_input() {
+    assert_eq!(
+        evaluate("1 2 + () . 4 ~ ,", 1000),
+        [
+            output_number(3).to_vec(),
+            vec![
+                mir::Instr::Push(number(4)),
+                mir::Instr::Command(mir::Command::InputChar),
+                mir::Instr::Command(mir::Command::OutputChar)
+            ]
+        ]
+        .concat()
+    );
+}
+
+#[test]
+fn stop_at_random() {
+    let output = evaluate("1 . 1 6 ! () .", 1000);
+
+    assert_eq!(output[..2], output_number(1));
+    assert_eq!(output.len(), 2 + 5);
+}
+
+#[test]
+fn stop_when_out_of_steps() {
+    let input = "loop: { loop () }\n1 . loop ()";
+
+    assert_eq!(instrs(&lower(input)[0])[2..], evaluate(input, 100)[2..]);
+    assert_eq!(evaluate(input, 100)[..2], output_number(1));
+}
+
+#[test]
+fn keep_captured_closures() {
+    // The closure left on the stack captures an argument, so can't be written back out as a push
+    let input = "f: x -> { g: { x . }\ng }\n1 f () ~ ()";
+
+    assert_eq!(evaluate(input, 1000), instrs(&lower(input)[0]));
+}
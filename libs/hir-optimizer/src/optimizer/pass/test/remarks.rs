@@ -0,0 +1,100 @@
+use super::{
+    super::{
+        block_inlining::BlockInlining, constant_folding::ConstantFolding, dead_block_elimination::DeadBlockElimination,
+        immediate_calls::ImmediateCalls, immediate_operations::ImmediateOperations,
+    },
+    *,
+};
+use crate::optimizer::remark::{RemarkKind, Remarks};
+
+const PASSES: [&dyn OptimizationPass; 5] = [
+    &ImmediateCalls,
+    &ImmediateOperations,
+    &ConstantFolding,
+    &BlockInlining,
+    &DeadBlockElimination,
+];
+
+fn remarks(input: &str) -> Vec<String> {
+    let options = Options::no_passes();
+    let mut context = OptimizationContext::with_remarks(lower(input), &options, Remarks::new(true));
+
+    // Run twice, as the optimizer would when looking for a fixpoint
+    for pass in PASSES.iter().chain(&PASSES) {
+        context.remarks().set_pass(pass.name());
+        pass.run(&mut context);
+    }
+
+    context
+        .into_remarks()
+        .into_remarks()
+        .iter()
+        .map(ToString::to_string)
+        .collect()
+}
+
+#[test]
+fn remark_folded_call() {
+    assert_eq!(
+        remarks("\n   2 3 + ()"),
+        [
+            "Immediate Call: called `+` directly at 2:8",
+            "Immediate Operation: combined call to `+` with its arguments at 2:8",
+            "Constant Folding: folded call to `+` at 2:8"
+        ]
+    );
+}
+
+#[test]
+fn remark_declined_fold() {
+    assert!(remarks("0 1 / ()").contains(&"Constant Folding: could not fold call to `/`: it divides by zero at 1:5".to_owned()));
+    assert!(remarks("1 6 ! ()").contains(&"Constant Folding: could not fold call to `!`: its result is random at 1:5".to_owned()));
+}
+
+#[test]
+fn remark_inlining() {
+    let remarks = remarks("cons: x -> { x 1 + () }\nloop: { 1 . loop () }\n5 cons () loop ()");
+
+    assert!(remarks.contains(&"Block Inlining: inlined `cons` at 3:3".to_owned()));
+    assert!(remarks.contains(&"Block Inlining: could not inline `loop`: recursive at 3:11".to_owned()));
+    assert!(remarks.contains(&"Dead Block Elimination: removed unused block `cons` at 1:12".to_owned()));
+}
+
+#[test]
+fn remarks_are_only_collected_when_enabled() {
+    let options = Options::no_passes();
+    let mut context = OptimizationContext::new(lower("2 3 + ()"), &options);
+
+    for pass in PASSES {
+        pass.run(&mut context);
+    }
+
+    assert!(context
+        .into_remarks()
+        .into_remarks()
+        .is_empty());
+}
+
+#[test]
+fn remark_json() {
+    let options = Options::no_passes();
+    let mut context = OptimizationContext::with_remarks(lower("2 3 + ()"), &options, Remarks::new(true));
+    context
+        .remarks()
+        .set_pass(ImmediateCalls.name());
+    ImmediateCalls.run(&mut context);
+
+    let remarks = context.into_remarks().into_remarks();
+
+    assert_eq!(remarks[0].kind, RemarkKind::Applied);
+    assert_eq!(
+        remarks[0].to_json(),
+        serde_json::json!({
+            "pass": "Immediate Call",
+            "kind": "applied",
+            "message": "called `+` directly",
+            "start": { "line": 1, "column": 5 },
+            "end": { "line": 1, "column": 6 },
+        })
+    );
+}
@@ -0,0 +1,57 @@
+use catastrophic_mir::mir;
+
+use super::{
+    super::{
+        block_inlining::BlockInlining, immediate_calls::ImmediateCalls, immediate_conditional_calls::ImmediateConditionalCalls,
+        immediate_operations::ImmediateOperations, tail_calls::TailCalls,
+    },
+    *,
+};
+
+const PASSES: [&dyn OptimizationPass; 4] = [&ImmediateCalls, &ImmediateOperations, &ImmediateConditionalCalls, &TailCalls];
+
+fn block_call(index: usize) -> mir::Instr {
+    mir::Instr::ImmediateCall(mir::Function::Block(index))
+}
+
+#[test]
+fn trailing_call_becomes_tail_call() {
+    let blocks = optimize("f: { 1 }\ng: { f () }\ng", &PASSES);
+    let f = labelled(&blocks, "f");
+
+    assert_eq!(instrs(&blocks[labelled(&blocks, "g")]), [mir::Instr::TailCall(mir::Function::Block(f))]);
+}
+
+#[test]
+fn earlier_calls_are_unchanged() {
+    let blocks = optimize("f: { 1 }\nf () 2", &PASSES);
+
+    assert_eq!(instrs(&blocks[0]), [block_call(labelled(&blocks, "f")), mir::Instr::Push(number(2))]);
+}
+
+#[test]
+fn builtin_calls_are_unchanged() {
+    assert_eq!(
+        instrs(&optimize("x -> { 1 x + () }", &[&ImmediateCalls, &TailCalls])[1]),
+        [
+            mir::Instr::Push(number(1)),
+            mir::Instr::Push(mir::Value::Arg(0)),
+            mir::Instr::ImmediateCall(mir::Function::BinOp(mir::BinOp::Plus))
+        ]
+    );
+}
+
+#[test]
+fn trailing_conditional_call_becomes_tail_call() {
+    let blocks = optimize("loop: n -> { {} { 1 n - () loop () } n 0 = () ? () () }", &PASSES);
+    let body = instrs(&blocks[labelled(&blocks, "loop")]);
+
+    assert!(matches!(body[..], [mir::Instr::ConditionalTailCall(..)]));
+}
+
+#[test]
+fn inlined_tail_calls_become_calls() {
+    let blocks = optimize("f: { 1 }\ng: { f () }\ng () 3", &[&ImmediateCalls, &TailCalls, &BlockInlining]);
+
+    assert_eq!(instrs(&blocks[0]), [block_call(labelled(&blocks, "f")), mir::Instr::Push(number(3))]);
+}
@@ -0,0 +1,114 @@
+use catastrophic_core::pretty::PrettyDebugger;
+use catastrophic_mir::mir;
+
+use super::*;
+use crate::optimizer::Optimizer;
+
+const PROGRAMS: [&str; 3] = [
+    "double: x -> { x x + () }\n3 double () .",
+    "count: n -> { n . { 1 n - () count () } {} n 0 = () ? () () }\n3 count ()",
+    "f: x -> y -> { g: { x y - () . }\ng }\n\"hi\" , , 1 -2 f () ()",
+];
+
+fn round_trip(blocks: &[mir::Block]) {
+    let text = mir::print(blocks);
+    let parsed = mir::parse(&text).unwrap();
+
+    assert_eq!(mir::print(&parsed), text);
+
+    for (block, parsed) in blocks.iter().zip(&parsed) {
+        assert_eq!(instrs(parsed), instrs(block));
+        assert_eq!(parsed.source, block.source);
+    }
+}
+
+#[test]
+fn round_trip_lowered() {
+    for program in PROGRAMS {
+        round_trip(&lower(program));
+    }
+}
+
+#[test]
+fn round_trip_optimized() {
+    let mut time_keeper = catastrophic_core::profiling::TimeKeeper::new(&"Test");
+
+    for program in PROGRAMS {
+        let options = Options::all_passes();
+        round_trip(&Optimizer::optimize_mir(&options, lower(program), &mut time_keeper.scope(&"Optimization")).unwrap());
+    }
+}
+
+#[test]
+fn parse_debug_output() {
+    let blocks = lower(PROGRAMS[0]);
+    let parsed = mir::parse(&PrettyDebugger(&blocks).to_string()).unwrap();
+
+    assert_eq!(mir::print(&parsed), mir::print(&blocks));
+}
+
+#[test]
+fn parse_every_instr() {
+    let text = "main(offset: 0, args: 0) {
+        Call
+        OutputChar
+        OutputNumber
+        InputChar
+        InputNumber
+        Push[-3]
+        Push[Random]
+        Push[IfThenElse]
+        Push[GreaterThan(Block(1), IfThenElse(1, 2, 3))]
+        Call[Block(1)]
+        (Arg(0) ? Plus() : Block(1)())
+        Tail(LessThan(1, 2) ? Block(1)() : Divide())
+    }
+    f(offset: 0, args: 0, direct) [parent: Block(0), label: f, span: 1:2-3:4] {
+        TailCall[Minus]
+    }";
+
+    let blocks = mir::parse(text).unwrap();
+
+    assert_eq!(
+        instrs(&blocks[0])[5..],
+        [
+            mir::Instr::Push(number(-3)),
+            mir::Instr::Push(mir::Value::Function(mir::Function::BinOp(mir::BinOp::Random))),
+            mir::Instr::Push(mir::Value::Function(mir::Function::TriOp(mir::TriOp::IfThenElse))),
+            mir::Instr::Push(bin_op(
+                mir::BinOp::GreaterThan,
+                mir::Value::Function(mir::Function::Block(1)),
+                mir::Value::ImmediateTriOp(mir::TriOp::IfThenElse, Box::new(number(1)), Box::new(number(2)), Box::new(number(3)))
+            )),
+            mir::Instr::ImmediateCall(mir::Function::Block(1)),
+            mir::Instr::ImmediateConditionalCall(mir::Value::Arg(0), mir::Function::BinOp(mir::BinOp::Plus), mir::Function::Block(1)),
+            mir::Instr::ConditionalTailCall(
+                bin_op(mir::BinOp::LessThan, number(1), number(2)),
+                mir::Function::Block(1),
+                mir::Function::BinOp(mir::BinOp::Divide)
+            ),
+        ]
+    );
+
+    assert!(!blocks[1].first_class);
+    assert_eq!(blocks[1].source.label.as_deref(), Some("f"));
+    assert_eq!(blocks[1].source.span, Some(Span::new(Location::new(1, 2), Location::new(3, 4), ())));
+    assert_eq!(mir::print(&blocks), mir::print(&mir::parse(&mir::print(&blocks)).unwrap()));
+}
+
+#[test]
+fn parse_errors() {
+    let error = |text: &str| {
+        mir::parse(text)
+            .unwrap_err()
+            .to_string()
+    };
+
+    assert_eq!(
+        error("main(offset: 0, args: 0) {\n    Push[1]\n    Jump\n}"),
+        "line 3: unknown instruction `Jump`"
+    );
+    assert_eq!(error("main(offset: 0, args: 0) {\n    Push[1"), "line 2: expected `]` but found ``");
+    assert_eq!(error("main(offset: 0, args: 0) {\n    Push[1]"), "line 2: block `main` is never closed");
+    assert_eq!(error("main {\n}"), "line 1: expected a block but found `main {`");
+}
@@ -0,0 +1,144 @@
+use catastrophic_core::profiling::TimeKeeper;
+use catastrophic_mir::mir::{self, VerifyErrorKind};
+
+use super::*;
+use crate::optimizer::{Optimizer, Step};
+
+struct BrokenPass;
+
+impl OptimizationPass for BrokenPass {
+    fn name(&self) -> &'static str {
+        "Broken"
+    }
+
+    fn run(&self, context: &mut OptimizationContext) -> bool {
+        let missing = context.blocks().len();
+        context
+            .block_mut(0)
+            .instrs
+            .push(span(mir::Instr::ImmediateCall(mir::Function::Block(missing))));
+        true
+    }
+}
+
+fn verify(blocks: &[mir::Block]) -> Option<(usize, Option<usize>, VerifyErrorKind)> {
+    mir::verify(blocks)
+        .err()
+        .map(|error| (error.block, error.instr, error.kind))
+}
+
+fn with_args(offset: usize, args: usize, instrs: Vec<mir::Instr>) -> mir::Block {
+    mir::Block {
+        offset,
+        args,
+        ..block(instrs)
+    }
+}
+
+#[test]
+fn lowered_program_is_valid() {
+    assert_eq!(verify(&lower("f: x -> { g: y -> { x y + () }\ng }\n1 2 f () () .")), None);
+}
+
+#[test]
+fn missing_block() {
+    let blocks = [block(vec![mir::Instr::Push(mir::Value::Function(mir::Function::Block(1)))])];
+
+    assert_eq!(verify(&blocks), Some((0, Some(0), VerifyErrorKind::MissingBlock(1))));
+}
+
+#[test]
+fn arg_out_of_range() {
+    let blocks = [
+        block(vec![]),
+        with_args(1, 1, vec![mir::Instr::Push(number(1)), mir::Instr::Push(mir::Value::Arg(2))]),
+    ];
+
+    assert_eq!(
+        verify(&blocks),
+        Some((1, Some(1), VerifyErrorKind::ArgOutOfRange { arg: 2, available: 2 }))
+    );
+}
+
+#[test]
+fn capture_out_of_range() {
+    let blocks = [block(vec![mir::Instr::ImmediateCall(mir::Function::Block(1))]), with_args(1, 0, vec![])];
+
+    assert_eq!(
+        verify(&blocks),
+        Some((
+            0,
+            Some(0),
+            VerifyErrorKind::CaptureOutOfRange {
+                block: 1,
+                offset: 1,
+                available: 0
+            }
+        ))
+    );
+}
+
+#[test]
+fn function_condition() {
+    let blocks = [block(vec![mir::Instr::ImmediateConditionalCall(
+        mir::Value::Function(mir::Function::BinOp(mir::BinOp::Plus)),
+        mir::Function::BinOp(mir::BinOp::Plus),
+        mir::Function::BinOp(mir::BinOp::Minus),
+    )])];
+
+    assert_eq!(verify(&blocks), Some((0, Some(0), VerifyErrorKind::FunctionCondition)));
+}
+
+#[test]
+fn misplaced_tail_call() {
+    let blocks = [block(vec![
+        mir::Instr::TailCall(mir::Function::BinOp(mir::BinOp::Plus)),
+        mir::Instr::Push(number(1)),
+    ])];
+
+    assert_eq!(verify(&blocks), Some((0, Some(0), VerifyErrorKind::MisplacedTailCall)));
+}
+
+#[test]
+fn entry_takes_args() {
+    assert_eq!(verify(&[with_args(0, 1, vec![])]), Some((0, None, VerifyErrorKind::EntryTakesArgs)));
+    assert_eq!(verify(&[]), Some((0, None, VerifyErrorKind::MissingEntry)));
+}
+
+#[test]
+fn context_names_pass() {
+    let options = Options::no_passes();
+    let mut context = OptimizationContext::new(vec![block(vec![])], &options);
+
+    BrokenPass.run(&mut context);
+
+    assert_eq!(
+        context
+            .verify("Broken")
+            .unwrap_err()
+            .to_string(),
+        "Broken produced malformed MIR: block 1 does not exist in block 0 at instruction 0"
+    );
+}
+
+#[test]
+fn optimizer_checks_after_each_pass() {
+    let options = Options::no_passes();
+    let mut context = OptimizationContext::new(vec![block(vec![])], &options);
+
+    let error = Optimizer::run_step(&Step::new(Box::new(BrokenPass)), &mut context).unwrap_err();
+
+    assert_eq!(error.pass, "Broken");
+    assert_eq!(error.error.kind, VerifyErrorKind::MissingBlock(1));
+}
+
+#[test]
+fn optimizer_reports_malformed_input() {
+    let mut time_keeper = TimeKeeper::new(&"Test");
+    let blocks = vec![with_args(0, 1, vec![])];
+
+    let error = Optimizer::optimize_mir(&Options::no_passes(), blocks, &mut time_keeper.scope(&"Optimization")).unwrap_err();
+
+    assert_eq!(error.pass, "Conversion");
+    assert_eq!(error.error.kind, VerifyErrorKind::EntryTakesArgs);
+}
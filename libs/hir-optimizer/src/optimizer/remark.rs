@@ -0,0 +1,136 @@
+use std::fmt::Display;
+
+use catastrophic_core::span::Span;
+use catastrophic_mir::mir;
+use serde_json::json;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RemarkKind {
+    Applied,
+    Declined,
+}
+
+/// A rewrite that a pass either made or decided against, and where in the source it happened
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Remark {
+    pub pass: &'static str,
+    pub kind: RemarkKind,
+    pub span: Span<()>,
+    pub message: String,
+}
+
+/// Collects the remarks made by passes, which are only formatted if they were asked for
+#[derive(Debug, Default)]
+pub struct Remarks {
+    enabled: bool,
+    pass: &'static str,
+    collected: Vec<Remark>,
+}
+
+impl Remark {
+    #[must_use]
+    pub fn to_json(&self) -> serde_json::Value {
+        let location = |location: catastrophic_core::span::Location| json!({ "line": location.line + 1, "column": location.col + 1 });
+
+        json!({
+            "pass": self.pass,
+            "kind": match self.kind {
+                RemarkKind::Applied => "applied",
+                RemarkKind::Declined => "declined",
+            },
+            "message": self.message,
+            "start": location(self.span.start),
+            "end": location(self.span.end),
+        })
+    }
+}
+
+impl Display for Remark {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: {} at {}:{}",
+            self.pass,
+            self.message,
+            self.span.start.line + 1,
+            self.span.start.col + 1
+        )
+    }
+}
+
+impl Remarks {
+    #[must_use]
+    pub fn new(enabled: bool) -> Self {
+        Self { enabled, ..Self::default() }
+    }
+
+    /// Attribute any following remarks to the given pass
+    pub fn set_pass(&mut self, pass: &'static str) {
+        self.pass = pass;
+    }
+
+    pub fn applied<S: Into<String>>(&mut self, span: Span<()>, message: impl FnOnce() -> S) {
+        self.push(RemarkKind::Applied, span, message);
+    }
+
+    pub fn declined<S: Into<String>>(&mut self, span: Span<()>, message: impl FnOnce() -> S) {
+        self.push(RemarkKind::Declined, span, message);
+    }
+
+    fn push<S: Into<String>>(&mut self, kind: RemarkKind, span: Span<()>, message: impl FnOnce() -> S) {
+        if !self.enabled {
+            return;
+        }
+
+        let remark = Remark {
+            pass: self.pass,
+            kind,
+            span,
+            message: message().into(),
+        };
+
+        // Passes are run until they settle, so see the same code more than once
+        if !self.collected.contains(&remark) {
+            self.collected.push(remark);
+        }
+    }
+
+    #[must_use]
+    pub fn into_remarks(self) -> Vec<Remark> {
+        self.collected
+    }
+}
+
+#[must_use]
+pub fn bin_op_symbol(op: mir::BinOp) -> &'static str {
+    match op {
+        mir::BinOp::Plus => "+",
+        mir::BinOp::Minus => "-",
+        mir::BinOp::Multiply => "*",
+        mir::BinOp::Divide => "/",
+        mir::BinOp::Equals => "=",
+        mir::BinOp::GreaterThan => ">",
+        mir::BinOp::LessThan => "<",
+        mir::BinOp::Random => "!",
+    }
+}
+
+#[must_use]
+pub fn tri_op_symbol(op: mir::TriOp) -> &'static str {
+    match op {
+        mir::TriOp::IfThenElse => "?",
+    }
+}
+
+/// Describe a function the way it would be written in source
+#[must_use]
+pub fn describe_function(blocks: &[mir::Block], function: mir::Function) -> String {
+    match function {
+        mir::Function::Block(index) => match &blocks[index].source.label {
+            Some(label) => format!("`{label}`"),
+            None => "anonymous block".to_owned(),
+        },
+        mir::Function::BinOp(op) => format!("`{}`", bin_op_symbol(op)),
+        mir::Function::TriOp(op) => format!("`{}`", tri_op_symbol(op)),
+    }
+}
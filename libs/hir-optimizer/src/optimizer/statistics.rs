@@ -0,0 +1,60 @@
+use std::{fmt::Display, ops::AddAssign};
+
+use catastrophic_core::span::Span;
+use catastrophic_mir::mir;
+
+/// How many instructions a pass has changed
+#[derive(Debug, Default, Copy, Clone, PartialEq, Eq)]
+pub struct Statistics {
+    pub removed: usize,
+    pub rewritten: usize,
+    pub added: usize,
+}
+
+impl Statistics {
+    /// Compare a block's instructions before and after a rewrite, ignoring those left untouched at either end
+    #[must_use]
+    pub fn compare(before: &[Span<mir::Instr>], after: &[Span<mir::Instr>]) -> Self {
+        let prefix = before
+            .iter()
+            .zip(after)
+            .take_while(|(x, y)| x == y)
+            .count();
+
+        let suffix = before[prefix..]
+            .iter()
+            .rev()
+            .zip(after[prefix..].iter().rev())
+            .take_while(|(x, y)| x == y)
+            .count();
+
+        let before = before.len() - prefix - suffix;
+        let after = after.len() - prefix - suffix;
+        let rewritten = before.min(after);
+
+        Self {
+            removed: before - rewritten,
+            rewritten,
+            added: after - rewritten,
+        }
+    }
+
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+}
+
+impl AddAssign for Statistics {
+    fn add_assign(&mut self, other: Self) {
+        self.removed += other.removed;
+        self.rewritten += other.rewritten;
+        self.added += other.added;
+    }
+}
+
+impl Display for Statistics {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} removed, {} rewritten, {} added", self.removed, self.rewritten, self.added)
+    }
+}
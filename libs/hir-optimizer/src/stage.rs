@@ -6,12 +6,17 @@ use catastrophic_core::{
 use catastrophic_hir::hir;
 use catastrophic_mir::mir;
 
-use crate::optimizer::{Optimizer, Options};
+use crate::optimizer::{remark::Remark, Optimizer, Options, PassError};
 
 pub struct OptimizationStage {
     options: Options,
 }
 
+/// Optimizes the program only to report what each pass did to it
+pub struct RemarksStage {
+    options: Options,
+}
+
 impl OptimizationStage {
     #[must_use]
     pub fn new(options: Options) -> Self {
@@ -19,17 +24,17 @@ impl OptimizationStage {
     }
 
     #[must_use]
-    pub fn pass_names() -> Vec<&'static str> {
-        Optimizer::pass_names()
+    pub fn pass_ids() -> Vec<String> {
+        Optimizer::pass_ids()
     }
 }
 
 impl Stage<Vec<hir::Block>> for OptimizationStage {
     type Output = Vec<mir::Block>;
-    type Error = NoError;
+    type Error = PassError;
 
     fn run(self, input: Vec<hir::Block>, time_scope: &mut TimeScope) -> Result<Self::Output, Self::Error> {
-        Ok(Optimizer::optimize_hir(self.options, input, time_scope))
+        Optimizer::optimize_hir(&self.options, input, time_scope)
     }
 
     fn name() -> &'static str {
@@ -41,11 +46,32 @@ impl Stage<Vec<hir::Block>> for OptimizationStage {
     }
 }
 
-#[derive(Debug)]
-pub enum NoError {}
+impl RemarksStage {
+    #[must_use]
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+}
+
+impl Stage<Vec<hir::Block>> for RemarksStage {
+    type Output = Vec<Remark>;
+    type Error = PassError;
+
+    fn run(self, input: Vec<hir::Block>, time_scope: &mut TimeScope) -> Result<Self::Output, Self::Error> {
+        Optimizer::remarks(&self.options, input, time_scope)
+    }
+
+    fn name() -> &'static str {
+        "Optimization Remarks"
+    }
+
+    fn error_context() -> &'static str {
+        "Unable to optimize input"
+    }
+}
 
-impl ErrorProvider for NoError {
-    fn write_errors(&self, _: &mut dyn ErrorWriter) -> std::fmt::Result {
-        Ok(())
+impl ErrorProvider for PassError {
+    fn write_errors(&self, writer: &mut dyn ErrorWriter) -> std::fmt::Result {
+        writer.error(None, &self.to_string())
     }
 }
@@ -1,4 +1,4 @@
-use std::collections::HashMap;
+use std::collections::BTreeMap;
 
 pub use catastrophic_ast::ast::{Builtin, Command};
 use catastrophic_core::{defines::ValueType, span::Span};
@@ -22,14 +22,33 @@ pub enum Instr {
     Push(Value),
 }
 
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct BlockSource {
+    pub parent: Option<usize>,
+    pub span: Option<Span<()>>,
+    pub label: Option<String>,
+}
+
 #[derive(Debug, Clone, PartialEq, Eq)]
 pub struct Block {
     pub offset: usize,
     pub args: usize,
     pub env: Vec<Value>,
-    pub symbols: HashMap<String, usize>,
+    pub symbols: BTreeMap<String, usize>,
     pub instrs: Vec<Span<Instr>>,
     pub name: String,
+    pub source: BlockSource,
+}
+
+impl BlockSource {
+    #[must_use]
+    pub fn new(parent: usize, span: Span<()>, label: Option<String>) -> Self {
+        Self {
+            parent: Some(parent),
+            span: Some(span),
+            label,
+        }
+    }
 }
 
 impl Block {
@@ -42,9 +61,10 @@ impl Block {
                 offset: 0,
                 args: args.len(),
                 env: Vec::new(),
-                symbols: HashMap::new(),
+                symbols: BTreeMap::new(),
                 instrs: Vec::new(),
                 name: name.clone(),
+                source: BlockSource::default(),
             },
             |parent| Self {
                 offset: parent.offset + parent.args,
@@ -53,6 +73,7 @@ impl Block {
                 symbols: parent.symbols.clone(),
                 instrs: Vec::new(),
                 name: name.clone(),
+                source: BlockSource::default(),
             },
         );
 
@@ -68,6 +89,12 @@ impl Block {
         block
     }
 
+    #[must_use]
+    pub fn with_source(mut self, source: BlockSource) -> Self {
+        self.source = source;
+        self
+    }
+
     pub fn push_symbol(&mut self, name: String, value: Value) {
         let index = self.env.len();
         self.env.push(value);
@@ -2,12 +2,38 @@ use std::fmt::Write;
 
 use catastrophic_core::pretty::{PrettyDebug, PrettyFormatter};
 
-use crate::hir::{Block, Command, Function, Instr, Value};
+use crate::hir::{Block, BlockSource, Command, Function, Instr, Value};
+
+impl PrettyDebug for BlockSource {
+    fn pretty_debug(&self, fmt: &mut PrettyFormatter) -> std::fmt::Result {
+        let mut details = Vec::new();
+
+        if let Some(parent) = self.parent {
+            details.push(format!("parent: Block({parent})"));
+        }
+
+        if let Some(label) = &self.label {
+            details.push(format!("label: {label}"));
+        }
+
+        if let Some(span) = self.span {
+            details.push(format!("span: {}:{}-{}:{}", span.start.line, span.start.col, span.end.line, span.end.col));
+        }
+
+        if details.is_empty() {
+            Ok(())
+        } else {
+            write!(fmt, " [{}]", details.join(", "))
+        }
+    }
+}
 
 impl PrettyDebug for Block {
     fn pretty_debug(&self, fmt: &mut PrettyFormatter) -> std::fmt::Result {
         fmt.write_indent()?;
-        writeln!(fmt, "{{")?;
+        write!(fmt, "{}", self.name)?;
+        self.source.pretty_debug(fmt)?;
+        writeln!(fmt, " {{")?;
         fmt.indent();
 
         for instr in &self.instrs {
@@ -0,0 +1,33 @@
+use rand::prelude::*;
+
+use catastrophic_core::{defines::ValueType, span::Span};
+use catastrophic_hir::hir::Builtin;
+
+use super::{error::RuntimeError, Arithmetic};
+
+/// Call a builtin which takes two numbers, reporting any result which can't be worked out as an error
+///
+/// Results which overflow are only reported when trapping, and otherwise wrap around
+pub fn apply(span: Span<()>, builtin: Builtin, a: ValueType, b: ValueType, arithmetic: Arithmetic) -> Result<ValueType, RuntimeError> {
+    let overflowing = |(result, overflowed): (ValueType, bool)| {
+        if overflowed && arithmetic == Arithmetic::Trapping {
+            Err(RuntimeError::Overflow(span, builtin))
+        } else {
+            Ok(result)
+        }
+    };
+
+    match builtin {
+        Builtin::Plus => overflowing(a.overflowing_add(b)),
+        Builtin::Minus => overflowing(a.overflowing_sub(b)),
+        Builtin::Multiply => overflowing(a.overflowing_mul(b)),
+        Builtin::Divide if b == 0 => Err(RuntimeError::DivisionByZero(span)),
+        Builtin::Divide => overflowing(a.overflowing_div(b)),
+        Builtin::Equals => Ok(ValueType::from(a == b)),
+        Builtin::GreaterThan => Ok(ValueType::from(a > b)),
+        Builtin::LessThan => Ok(ValueType::from(a < b)),
+        Builtin::Random if a > b => Err(RuntimeError::EmptyRandomRange(span, a, b)),
+        Builtin::Random => Ok(thread_rng().gen_range(a..=b)),
+        Builtin::IfThenElse => Err(RuntimeError::InvalidArgsForBuiltin(span, builtin)),
+    }
+}
@@ -0,0 +1,32 @@
+use std::rc::Rc;
+
+/// A value which may refer to a closure, so closures holding it know what to free with them
+pub trait Captured: Sized {
+    /// The closure this value refers to, if it is one
+    fn into_closure(self) -> Option<Rc<Closure<Self>>>;
+}
+
+/// A block along with the args it captured, which is freed as soon as the last value referring to it is dropped
+///
+/// A closure can only capture values which existed before it, so closures can never refer to each other in a cycle
+#[derive(Debug, Clone)]
+pub struct Closure<V: Captured> {
+    pub block: usize,
+    pub args: Vec<V>,
+}
+
+impl<V: Captured> Drop for Closure<V> {
+    fn drop(&mut self) {
+        // Closures captured inside one another are freed in a loop, as a long chain of them would otherwise recurse
+        // once per closure
+        let mut args = std::mem::take(&mut self.args);
+
+        while let Some(value) = args.pop() {
+            if let Some(closure) = value.into_closure() {
+                if let Ok(mut closure) = Rc::try_unwrap(closure) {
+                    args.append(&mut closure.args);
+                }
+            }
+        }
+    }
+}
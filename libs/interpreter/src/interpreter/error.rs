@@ -1,4 +1,5 @@
 use catastrophic_core::{
+    defines::ValueType,
     error::{context::ErrorProvider, writer::ErrorWriter},
     span::Span,
 };
@@ -12,6 +13,10 @@ pub enum RuntimeError {
     InvalidArgsForBuiltin(Span<()>, Builtin),
     InsufficientArgsForFunction(Span<()>),
     OutputFunction(Span<()>),
+    CallDepthExceeded(Span<()>, usize),
+    DivisionByZero(Span<()>),
+    Overflow(Span<()>, Builtin),
+    EmptyRandomRange(Span<()>, ValueType, ValueType),
 }
 
 impl ErrorProvider for RuntimeError {
@@ -25,6 +30,18 @@ impl ErrorProvider for RuntimeError {
             }
             RuntimeError::InsufficientArgsForFunction(span) => writer.error(Some(span), "Attempted to call a function with insufficient arguments"),
             RuntimeError::OutputFunction(span) => writer.error(Some(span), "Attempted to output a function as a value"),
+            RuntimeError::CallDepthExceeded(span, depth) => {
+                writer.error(Some(span), &format!("Attempted to call a block more than {depth} calls deep"))
+            }
+            RuntimeError::DivisionByZero(span) => writer.error(Some(span), "Attempted to divide by zero"),
+            RuntimeError::Overflow(span, builtin) => writer.error(
+                Some(span),
+                &format!("Result of calling builtin function `{builtin}` is too large to be represented"),
+            ),
+            RuntimeError::EmptyRandomRange(span, low, high) => writer.error(
+                Some(span),
+                &format!("Attempted to choose a random number from {low} to {high}, which is an empty range"),
+            ),
         }
     }
 }
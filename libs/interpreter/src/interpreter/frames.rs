@@ -0,0 +1,59 @@
+use catastrophic_core::span::Span;
+
+use super::error::RuntimeError;
+
+/// A block part way through running, which is returned to once the block it called has finished
+#[derive(Debug, Clone)]
+pub struct Frame<V> {
+    block: usize,
+    args: Vec<V>,
+    instr: usize,
+}
+
+/// Runs the instructions of a program, leaving the frames of the blocks they call to [`run`]
+pub trait Interpret<'a> {
+    type Value;
+    type Instr: 'a;
+
+    /// The instructions of a block, which are empty if the block doesn't exist
+    fn instrs(&self, block: usize) -> &'a [Span<Self::Instr>];
+
+    /// Run a single instruction, returning the frame of any block it calls
+    fn instr(&mut self, instr: &Span<Self::Instr>, args: &[Self::Value]) -> Result<Option<Frame<Self::Value>>, RuntimeError>;
+}
+
+impl<V> Frame<V> {
+    pub fn new(block: usize, args: Vec<V>) -> Self {
+        Self { block, args, instr: 0 }
+    }
+}
+
+/// Run a program from its first block, with the frames kept on the heap, so how deeply blocks can call each other is
+/// only limited by memory or the maximum depth
+pub fn run<'a, I: Interpret<'a>>(interpreter: &mut I, max_depth: Option<usize>) -> Result<(), RuntimeError> {
+    let mut frames = vec![Frame::new(0, Vec::new())];
+
+    while let Some(frame) = frames.last_mut() {
+        let instrs = interpreter.instrs(frame.block);
+
+        let Some(instr) = instrs.get(frame.instr) else {
+            frames.pop();
+            continue;
+        };
+
+        frame.instr += 1;
+
+        if let Some(callee) = interpreter.instr(instr, &frame.args)? {
+            // A call at the end of a block can take over its frame, so loops written as recursion run in constant space
+            if frame.instr == instrs.len() {
+                *frame = callee;
+            } else if max_depth.is_some_and(|max_depth| frames.len() >= max_depth) {
+                return Err(RuntimeError::CallDepthExceeded(instr.swap(()), frames.len()));
+            } else {
+                frames.push(callee);
+            }
+        }
+    }
+
+    Ok(())
+}
@@ -0,0 +1,28 @@
+use std::io::{stdin, stdout, Read, Write};
+
+use catastrophic_core::defines::ValueType;
+
+// TODO: Error handling here
+pub fn output_char(value: ValueType) {
+    let _ = stdout().write(&[value as u8]).unwrap();
+}
+
+pub fn output_number(value: ValueType) {
+    print!("{value}");
+}
+
+// TODO: Error handling here
+pub fn input_char() -> ValueType {
+    stdout().flush().unwrap();
+    let mut buffer = [b'\0'];
+    stdin().read_exact(&mut buffer).unwrap();
+    buffer[0] as char as ValueType
+}
+
+// TODO: Error handling here
+pub fn input_number() -> ValueType {
+    stdout().flush().unwrap();
+    let mut buffer = String::new();
+    stdin().read_line(&mut buffer).unwrap();
+    buffer.trim().parse().unwrap()
+}
@@ -0,0 +1,229 @@
+use std::rc::Rc;
+
+use catastrophic_core::{defines::ValueType, span::Span};
+use catastrophic_hir::hir::Builtin;
+use catastrophic_mir::mir::{self, BinOp, Command, Function, Instr, TriOp};
+
+use super::{
+    arithmetic,
+    closure::{Captured, Closure},
+    error::RuntimeError,
+    frames::{self, Frame, Interpret},
+    io, Arithmetic, Options,
+};
+
+#[derive(Debug, Clone)]
+enum Value {
+    BinOp(BinOp),
+    TriOp(TriOp),
+    Closure(Rc<Closure<Value>>),
+    Number(ValueType),
+}
+
+struct Machine<'a> {
+    blocks: &'a [mir::Block],
+    stack: Vec<Value>,
+    arithmetic: Arithmetic,
+}
+
+#[derive(Debug, Clone)]
+pub struct MirState {
+    blocks: Vec<mir::Block>,
+}
+
+/// The builtin an operator was lowered from, to report errors the same way as for HIR
+fn builtin(function: Function) -> Builtin {
+    match function {
+        Function::BinOp(op) => match op {
+            BinOp::Plus => Builtin::Plus,
+            BinOp::Minus => Builtin::Minus,
+            BinOp::Multiply => Builtin::Multiply,
+            BinOp::Divide => Builtin::Divide,
+            BinOp::Equals => Builtin::Equals,
+            BinOp::GreaterThan => Builtin::GreaterThan,
+            BinOp::LessThan => Builtin::LessThan,
+            BinOp::Random => Builtin::Random,
+        },
+        Function::TriOp(TriOp::IfThenElse) => Builtin::IfThenElse,
+        Function::Block(_) => unreachable!("blocks are not builtins"),
+    }
+}
+
+fn apply_bin_op(span: Span<()>, op: BinOp, x: Value, y: Value, arithmetic: Arithmetic) -> Result<Value, RuntimeError> {
+    let builtin = builtin(Function::BinOp(op));
+
+    let (Value::Number(a), Value::Number(b)) = (x, y) else {
+        return Err(RuntimeError::InvalidArgsForBuiltin(span, builtin));
+    };
+
+    arithmetic::apply(span, builtin, a, b, arithmetic).map(Value::Number)
+}
+
+fn apply_tri_op(span: Span<()>, op: TriOp, x: Value, y: Value, z: Value) -> Result<Value, RuntimeError> {
+    match (op, x) {
+        (TriOp::IfThenElse, Value::Number(i)) => Ok(if i == ValueType::from(false) { z } else { y }),
+        (TriOp::IfThenElse, _) => Err(RuntimeError::InvalidArgsForBuiltin(span, Builtin::IfThenElse)),
+    }
+}
+
+impl Captured for Value {
+    fn into_closure(self) -> Option<Rc<Closure<Self>>> {
+        match self {
+            Value::Closure(closure) => Some(closure),
+            _ => None,
+        }
+    }
+}
+
+impl<'a> Machine<'a> {
+    fn new(blocks: &'a [mir::Block], options: &Options) -> Self {
+        Self {
+            blocks,
+            stack: Vec::new(),
+            arithmetic: options.arithmetic(),
+        }
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack
+            .pop()
+            .unwrap_or(Value::Number(0))
+    }
+
+    fn pop_number(&mut self, span: Span<()>) -> Result<ValueType, RuntimeError> {
+        match self.pop() {
+            Value::Number(value) => Ok(value),
+            _ => Err(RuntimeError::OutputFunction(span)),
+        }
+    }
+
+    /// Make a function into a value, capturing the leading arguments of the current block if it's a block
+    fn function(&mut self, span: Span<()>, function: Function, args: &[Value]) -> Result<Value, RuntimeError> {
+        Ok(match function {
+            Function::Block(index) => {
+                let Some(block) = self.blocks.get(index) else {
+                    return Err(RuntimeError::CalledInvalidBlock(span));
+                };
+
+                Value::Closure(Rc::new(Closure {
+                    block: index,
+                    args: args[..block.offset].to_vec(),
+                }))
+            }
+            Function::BinOp(op) => Value::BinOp(op),
+            Function::TriOp(op) => Value::TriOp(op),
+        })
+    }
+
+    fn value(&mut self, span: Span<()>, value: &mir::Value, args: &[Value]) -> Result<Value, RuntimeError> {
+        match value {
+            mir::Value::Arg(index) => Ok(args[*index].clone()),
+            mir::Value::Number(value) => Ok(Value::Number(*value)),
+            mir::Value::Function(function) => self.function(span, *function, args),
+            mir::Value::ImmediateBinOp(op, x, y) => {
+                let (x, y) = (self.value(span, x, args)?, self.value(span, y, args)?);
+                apply_bin_op(span, *op, x, y, self.arithmetic)
+            }
+            mir::Value::ImmediateTriOp(op, x, y, z) => {
+                let (x, y, z) = (self.value(span, x, args)?, self.value(span, y, args)?, self.value(span, z, args)?);
+                apply_tri_op(span, *op, x, y, z)
+            }
+        }
+    }
+
+    /// Call a function with its args from the stack, calling it straight away if it's a builtin
+    ///
+    /// Blocks are returned as a frame for the caller to run, which can take the place of its own frame when the call is
+    /// the last thing it does
+    fn call(&mut self, span: Span<()>, function: Value) -> Result<Option<Frame<Value>>, RuntimeError> {
+        match function {
+            Value::Number(_) => Err(RuntimeError::CalledNumber(span)),
+            Value::BinOp(op) => {
+                let (x, y) = (self.pop(), self.pop());
+                let result = apply_bin_op(span, op, x, y, self.arithmetic)?;
+                self.stack.push(result);
+                Ok(None)
+            }
+            Value::TriOp(op) => {
+                let (x, y, z) = (self.pop(), self.pop(), self.pop());
+                let result = apply_tri_op(span, op, x, y, z)?;
+                self.stack.push(result);
+                Ok(None)
+            }
+            Value::Closure(closure) => {
+                let (block, mut args) = (closure.block, closure.args.clone());
+
+                for _ in 0..self.blocks[block].args {
+                    let arg = self.pop();
+                    args.push(arg);
+                }
+
+                Ok(Some(Frame::new(block, args)))
+            }
+        }
+    }
+}
+
+impl<'a> Interpret<'a> for Machine<'a> {
+    type Value = Value;
+    type Instr = Instr;
+
+    fn instrs(&self, block: usize) -> &'a [Span<Instr>] {
+        &self.blocks[block].instrs
+    }
+
+    fn instr(&mut self, instr: &Span<Instr>, args: &[Value]) -> Result<Option<Frame<Value>>, RuntimeError> {
+        let span = instr.swap(());
+
+        let function = match &instr.data {
+            Instr::Push(value) => {
+                let value = self.value(span, value, args)?;
+                self.stack.push(value);
+                return Ok(None);
+            }
+            Instr::Command(command) => match command {
+                Command::Call => self.pop(),
+                Command::OutputChar => {
+                    io::output_char(self.pop_number(span)?);
+                    return Ok(None);
+                }
+                Command::OutputNumber => {
+                    io::output_number(self.pop_number(span)?);
+                    return Ok(None);
+                }
+                Command::InputChar => {
+                    self.stack
+                        .push(Value::Number(io::input_char()));
+                    return Ok(None);
+                }
+                Command::InputNumber => {
+                    self.stack
+                        .push(Value::Number(io::input_number()));
+                    return Ok(None);
+                }
+            },
+            Instr::ImmediateCall(function) | Instr::TailCall(function) => self.function(span, *function, args)?,
+            Instr::ImmediateConditionalCall(value, x, y) | Instr::ConditionalTailCall(value, x, y) => {
+                let function = match self.value(span, value, args)? {
+                    Value::Number(condition) if condition == ValueType::from(false) => *y,
+                    Value::Number(_) => *x,
+                    _ => return Err(RuntimeError::InvalidArgsForBuiltin(span, Builtin::IfThenElse)),
+                };
+
+                self.function(span, function, args)?
+            }
+        };
+
+        self.call(span, function)
+    }
+}
+
+impl MirState {
+    pub fn new(blocks: Vec<mir::Block>) -> Self {
+        Self { blocks }
+    }
+
+    pub fn interpret(&mut self, options: &Options) -> Result<(), RuntimeError> {
+        frames::run(&mut Machine::new(&self.blocks, options), options.max_depth())
+    }
+}
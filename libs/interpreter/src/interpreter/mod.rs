@@ -1,16 +1,71 @@
 use catastrophic_hir::hir;
+use catastrophic_mir::mir;
 
-use self::state::State;
+use self::{mir_state::MirState, state::State};
 
 pub use self::error::RuntimeError;
 
+mod arithmetic;
+mod closure;
 mod error;
+mod frames;
+mod io;
+mod mir_state;
 mod state;
+mod test;
+
+/// What happens when the result of an arithmetic builtin is too large to be represented
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum Arithmetic {
+    /// Stop the program with an error
+    #[default]
+    Trapping,
+    /// Wrap around to the other end of the range of values
+    Wrapping,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    max_depth: Option<usize>,
+    arithmetic: Arithmetic,
+}
 
 pub struct Interpreter;
 
+impl Options {
+    /// Limit how many blocks may be part way through running at once, which is otherwise only limited by memory
+    ///
+    /// Calls at the end of a block take the place of the block, so don't count towards the depth
+    #[must_use]
+    pub fn with_max_depth(mut self, max_depth: Option<usize>) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    #[must_use]
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    #[must_use]
+    pub fn with_arithmetic(mut self, arithmetic: Arithmetic) -> Self {
+        self.arithmetic = arithmetic;
+        self
+    }
+
+    #[must_use]
+    pub fn arithmetic(&self) -> Arithmetic {
+        self.arithmetic
+    }
+}
+
 impl Interpreter {
-    pub fn interpret(ir: Vec<hir::Block>) -> Result<(), RuntimeError> {
-        State::new(ir).interpret()
+    pub fn interpret(ir: Vec<hir::Block>, options: &Options) -> Result<(), RuntimeError> {
+        State::new(ir).interpret(options)
+    }
+
+    /// Run a program after it has been lowered to MIR, and possibly optimized
+    pub fn interpret_mir(ir: Vec<mir::Block>, options: &Options) -> Result<(), RuntimeError> {
+        MirState::new(ir).interpret(options)
     }
 }
@@ -1,16 +1,20 @@
-use std::io::{stdin, stdout, Read, Write};
-
-use rand::prelude::*;
+use std::rc::Rc;
 
 use catastrophic_core::{defines::ValueType, span::Span};
 use catastrophic_hir::hir::{self, Builtin, Command};
 
-use super::error::RuntimeError;
+use super::{
+    arithmetic,
+    closure::{Captured, Closure},
+    error::RuntimeError,
+    frames::{self, Frame, Interpret},
+    io, Arithmetic, Options,
+};
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 enum Value {
     Builtin(Builtin),
-    Closure(usize),
+    Closure(Rc<Closure<Value>>),
     Number(ValueType),
 }
 
@@ -20,16 +24,10 @@ enum CallableFunction {
     Block(usize),
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Clone)]
 enum StackFunction {
     Builtin(Builtin),
-    Closure(usize),
-}
-
-#[derive(Debug, Clone)]
-struct Closure {
-    block: usize,
-    args: Vec<Value>,
+    Closure(Rc<Closure<Value>>),
 }
 
 #[derive(Debug, Clone)]
@@ -37,24 +35,15 @@ struct Stack {
     stack: Vec<Value>,
 }
 
-struct Env<'a> {
+struct Machine<'a> {
     blocks: &'a [hir::Block],
     stack: &'a mut Stack,
-    closures: &'a mut Closures,
-    args: Vec<Value>,
-    block: usize,
-    instr: usize,
-}
-
-#[derive(Debug, Clone)]
-struct Closures {
-    closures: Vec<Closure>,
+    arithmetic: Arithmetic,
 }
 
 #[derive(Debug, Clone)]
 pub struct State {
     blocks: Vec<hir::Block>,
-    closures: Closures,
     stack: Stack,
 }
 
@@ -74,115 +63,47 @@ impl Stack {
     }
 }
 
-impl Closures {
-    pub fn new() -> Self {
-        Self { closures: Vec::new() }
-    }
-
-    pub fn push(&mut self, block: usize, args: Vec<Value>) -> usize {
-        let len = self.closures.len();
-
-        self.closures
-            .push(Closure { block, args });
-
-        len
-    }
-
-    pub fn get(&self, index: usize) -> Option<&Closure> {
-        self.closures.get(index)
+impl Captured for Value {
+    fn into_closure(self) -> Option<Rc<Closure<Self>>> {
+        match self {
+            Value::Closure(closure) => Some(closure),
+            _ => None,
+        }
     }
 }
 
-impl<'a> Env<'a> {
-    fn new(blocks: &'a [hir::Block], stack: &'a mut Stack, closures: &'a mut Closures, args: Vec<Value>, block: usize) -> Self {
+impl<'a> Machine<'a> {
+    fn new(blocks: &'a [hir::Block], stack: &'a mut Stack, options: &Options) -> Self {
         Self {
             blocks,
             stack,
-            closures,
-            args,
-            block,
-            instr: 0,
+            arithmetic: options.arithmetic(),
         }
     }
 
     fn call_builtin(&mut self, span: Span<()>, args: &[Value], builtin: Builtin) -> Result<(), RuntimeError> {
-        let result = match builtin {
-            Builtin::Plus => {
-                if let [Value::Number(a), Value::Number(b)] = args[..] {
-                    Ok(Value::Number(a + b))
-                } else {
-                    Err(())
-                }
-            }
-            Builtin::Minus => {
-                if let [Value::Number(a), Value::Number(b)] = args[..] {
-                    Ok(Value::Number(a - b))
-                } else {
-                    Err(())
-                }
-            }
-            Builtin::Multiply => {
-                if let [Value::Number(a), Value::Number(b)] = args[..] {
-                    Ok(Value::Number(a * b))
-                } else {
-                    Err(())
-                }
-            }
-            Builtin::Divide => {
-                if let [Value::Number(a), Value::Number(b)] = args[..] {
-                    Ok(Value::Number(a / b))
+        let result = match (builtin, args) {
+            (Builtin::IfThenElse, [Value::Number(i), t, e]) => {
+                if *i == ValueType::from(false) {
+                    e.clone()
                 } else {
-                    Err(())
+                    t.clone()
                 }
             }
-            Builtin::LessThan => {
-                if let [Value::Number(a), Value::Number(b)] = args[..] {
-                    Ok(Value::Number(ValueType::from(a < b)))
-                } else {
-                    Err(())
-                }
-            }
-            Builtin::GreaterThan => {
-                if let [Value::Number(a), Value::Number(b)] = args[..] {
-                    Ok(Value::Number(ValueType::from(a > b)))
-                } else {
-                    Err(())
-                }
-            }
-            Builtin::Equals => {
-                if let [Value::Number(a), Value::Number(b)] = args[..] {
-                    Ok(Value::Number(ValueType::from(a == b)))
-                } else {
-                    Err(())
-                }
-            }
-            Builtin::Random => {
-                if let [Value::Number(a), Value::Number(b)] = args[..] {
-                    let mut rng = thread_rng();
-                    Ok(Value::Number(ValueType::from(rng.gen_range(a..=b))))
-                } else {
-                    Err(())
-                }
-            }
-            Builtin::IfThenElse => {
-                if let [Value::Number(i), t, e] = args[..] {
-                    Ok(if i == ValueType::from(false) { e } else { t })
-                } else {
-                    Err(())
-                }
-            }
-        }
-        .map_err(|()| RuntimeError::InvalidArgsForBuiltin(span, builtin))?;
+            (Builtin::IfThenElse, _) => return Err(RuntimeError::InvalidArgsForBuiltin(span, builtin)),
+            (_, [Value::Number(a), Value::Number(b)]) => Value::Number(arithmetic::apply(span, builtin, *a, *b, self.arithmetic)?),
+            _ => return Err(RuntimeError::InvalidArgsForBuiltin(span, builtin)),
+        };
 
         self.stack.push(result);
         Ok(())
     }
 
-    fn call_block(&mut self, args: Vec<Value>, block: usize) -> Result<(), RuntimeError> {
-        Env::new(self.blocks, self.stack, self.closures, args, block).run()
-    }
-
-    fn call_instr(&mut self, span: Span<()>) -> Result<(), RuntimeError> {
+    /// Pop a function from the stack along with its args, calling it straight away if it's a builtin
+    ///
+    /// Blocks are returned as a frame for the caller to run, which can take the place of its own frame when the call is
+    /// the last thing it does
+    fn call_instr(&mut self, span: Span<()>) -> Result<Option<Frame<Value>>, RuntimeError> {
         let function = match self.stack.pop() {
             Value::Builtin(builtin) => StackFunction::Builtin(builtin),
             Value::Closure(closure) => StackFunction::Closure(closure),
@@ -206,11 +127,8 @@ impl<'a> Env<'a> {
                 },
                 CallableFunction::Builtin(builtin),
             ),
-            StackFunction::Closure(closure) => match self.closures.get(closure) {
-                Some(closure) => match self.blocks.get(closure.block) {
-                    Some(block) => (closure.args.clone(), block.args, CallableFunction::Block(closure.block)),
-                    None => return Err(RuntimeError::CalledInvalidBlock(span)),
-                },
+            StackFunction::Closure(closure) => match self.blocks.get(closure.block) {
+                Some(block) => (closure.args.clone(), block.args, CallableFunction::Block(closure.block)),
                 None => return Err(RuntimeError::CalledInvalidBlock(span)),
             },
         };
@@ -222,16 +140,17 @@ impl<'a> Env<'a> {
         }
 
         match callable {
-            CallableFunction::Builtin(builtin) => self.call_builtin(span, &args, builtin),
-            CallableFunction::Block(block) => self.call_block(args, block),
+            CallableFunction::Builtin(builtin) => self
+                .call_builtin(span, &args, builtin)
+                .map(|()| None),
+            CallableFunction::Block(block) => Ok(Some(Frame::new(block, args))),
         }
     }
 
     fn output_char_instr(&mut self, span: Span<()>) -> Result<(), RuntimeError> {
-        // TODO: Error handling here
         match self.stack.pop() {
             Value::Number(value) => {
-                let _ = stdout().write(&[value as u8]).unwrap();
+                io::output_char(value);
                 Ok(())
             }
             _ => Err(RuntimeError::OutputFunction(span)),
@@ -239,10 +158,9 @@ impl<'a> Env<'a> {
     }
 
     fn output_number_instr(&mut self, span: Span<()>) -> Result<(), RuntimeError> {
-        // TODO: Error handling here
         match self.stack.pop() {
             Value::Number(value) => {
-                print!("{value}");
+                io::output_number(value);
                 Ok(())
             }
             _ => Err(RuntimeError::OutputFunction(span)),
@@ -250,73 +168,66 @@ impl<'a> Env<'a> {
     }
 
     fn input_char_instr(&mut self) {
-        // TODO: Error handling here
-        stdout().flush().unwrap();
-        let mut buffer = [b'\0'];
-        stdin().read_exact(&mut buffer).unwrap();
         self.stack
-            .push(Value::Number(buffer[0] as char as ValueType));
+            .push(Value::Number(io::input_char()));
     }
 
     fn input_number_instr(&mut self) {
-        // TODO: Error handling here
-        stdout().flush().unwrap();
-        let mut buffer = String::new();
-        stdin().read_line(&mut buffer).unwrap();
         self.stack
-            .push(Value::Number(buffer.trim().parse().unwrap()));
+            .push(Value::Number(io::input_number()));
     }
+}
 
-    fn run(&mut self) -> Result<(), RuntimeError> {
-        while let Some(instr) = self
-            .blocks
-            .get(self.block)
-            .and_then(|block| block.instrs.get(self.instr))
-        {
-            let instr_span = instr.swap(());
-            match instr.data {
-                hir::Instr::Command(command) => match command {
-                    Command::Call => self.call_instr(instr_span)?,
-                    Command::OutputChar => self.output_char_instr(instr_span)?,
-                    Command::OutputNumber => self.output_number_instr(instr_span)?,
-                    Command::InputChar => self.input_char_instr(),
-                    Command::InputNumber => self.input_number_instr(),
-                },
-                hir::Instr::Push(value) => match value {
-                    hir::Value::Arg(index) => self.stack.push(self.args[index]),
-                    hir::Value::Number(value) => self.stack.push(Value::Number(value)),
-                    hir::Value::Function(function) => match function {
-                        hir::Function::Builtin(builtin) => self.stack.push(Value::Builtin(builtin)),
-                        hir::Function::Block(index) => {
-                            let args = match self.blocks.get(index) {
-                                Some(block) => self.args[0..block.offset].to_owned(),
-                                None => return Err(RuntimeError::CalledInvalidBlock(instr_span)),
-                            };
-
-                            self.stack
-                                .push(Value::Closure(self.closures.push(index, args)));
-                        }
-                    },
-                },
-            }
+impl<'a> Interpret<'a> for Machine<'a> {
+    type Value = Value;
+    type Instr = hir::Instr;
 
-            self.instr += 1;
+    fn instrs(&self, block: usize) -> &'a [Span<hir::Instr>] {
+        match self.blocks.get(block) {
+            Some(block) => &block.instrs,
+            None => &[],
         }
+    }
 
-        Ok(())
+    fn instr(&mut self, instr: &Span<hir::Instr>, args: &[Value]) -> Result<Option<Frame<Value>>, RuntimeError> {
+        let span = instr.swap(());
+
+        match instr.data {
+            hir::Instr::Command(command) => match command {
+                Command::Call => return self.call_instr(span),
+                Command::OutputChar => self.output_char_instr(span)?,
+                Command::OutputNumber => self.output_number_instr(span)?,
+                Command::InputChar => self.input_char_instr(),
+                Command::InputNumber => self.input_number_instr(),
+            },
+            hir::Instr::Push(value) => match value {
+                hir::Value::Arg(index) => self.stack.push(args[index].clone()),
+                hir::Value::Number(value) => self.stack.push(Value::Number(value)),
+                hir::Value::Function(function) => match function {
+                    hir::Function::Builtin(builtin) => self.stack.push(Value::Builtin(builtin)),
+                    hir::Function::Block(index) => {
+                        let args = match self.blocks.get(index) {
+                            Some(block) => args[0..block.offset].to_owned(),
+                            None => return Err(RuntimeError::CalledInvalidBlock(span)),
+                        };
+
+                        self.stack
+                            .push(Value::Closure(Rc::new(Closure { block: index, args })));
+                    }
+                },
+            },
+        }
+
+        Ok(None)
     }
 }
 
 impl State {
     pub fn new(blocks: Vec<hir::Block>) -> Self {
-        Self {
-            blocks,
-            stack: Stack::new(),
-            closures: Closures::new(),
-        }
+        Self { blocks, stack: Stack::new() }
     }
 
-    pub fn interpret(&mut self) -> Result<(), RuntimeError> {
-        Env::new(&self.blocks, &mut self.stack, &mut self.closures, Vec::new(), 0).run()
+    pub fn interpret(&mut self, options: &Options) -> Result<(), RuntimeError> {
+        frames::run(&mut Machine::new(&self.blocks, &mut self.stack, options), options.max_depth())
     }
 }
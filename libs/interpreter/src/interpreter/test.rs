@@ -0,0 +1,131 @@
+#![cfg(test)]
+
+use std::{
+    alloc::{GlobalAlloc, Layout, System},
+    cell::Cell,
+};
+
+use catastrophic_analyser::analyser::Analyser;
+use catastrophic_core::profiling::TimeKeeper;
+use catastrophic_hir_optimizer::optimizer::{Optimizer, Options as OptimizerOptions};
+use catastrophic_parser::parser::Parser;
+
+use super::*;
+
+/// Keeps track of how much memory the current thread has allocated, so tests running alongside each other don't count
+/// towards each other's usage
+struct CountingAllocator;
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+thread_local! {
+    static ALLOCATED: Cell<isize> = const { Cell::new(0) };
+    static PEAK: Cell<isize> = const { Cell::new(0) };
+}
+
+fn track(size: isize) {
+    let _ = ALLOCATED.try_with(|allocated| {
+        allocated.set(allocated.get() + size);
+        let _ = PEAK.try_with(|peak| peak.set(peak.get().max(allocated.get())));
+    });
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        let ptr = unsafe { System.alloc(layout) };
+
+        if !ptr.is_null() {
+            track(layout.size() as isize);
+        }
+
+        ptr
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) };
+        track(-(layout.size() as isize));
+    }
+}
+
+/// The most memory allocated at once while running the function, on top of what was already allocated
+fn peak_memory(f: impl FnOnce()) -> isize {
+    let start = ALLOCATED.with(Cell::get);
+    PEAK.with(|peak| peak.set(start));
+
+    f();
+
+    PEAK.with(Cell::get) - start
+}
+
+fn analyse(input: &str) -> Vec<hir::Block> {
+    let ast = Parser::with_str(input)
+        .parse()
+        .unwrap()
+        .ast;
+
+    Analyser::analyse_ast(ast).unwrap()
+}
+
+fn lower(input: &str) -> Vec<mir::Block> {
+    let mut time_keeper = TimeKeeper::new(&"Test");
+    Optimizer::optimize_hir(&OptimizerOptions::no_passes(), analyse(input), &mut time_keeper.scope(&"Conversion")).unwrap()
+}
+
+/// A loop which makes two new closures on every iteration, only one of which it calls
+fn closure_loop(iterations: usize) -> String {
+    format!(
+        "count: n -> {{
+            {{ }}
+            {{ 1 n - () count () }}
+            n ? () ()
+        }}
+
+        {iterations} count ()"
+    )
+}
+
+/// A loop which makes a chain of closures, each capturing the one before it, which is all dropped at once at the end
+fn closure_chain(length: usize) -> String {
+    format!(
+        "chain: n -> previous -> {{
+            {{ }}
+            {{ 1 n - () {{ previous }} chain () }}
+            n ? () ()
+        }}
+
+        {length} 0 chain ()"
+    )
+}
+
+#[test]
+fn hir_closures_are_freed_once_unreachable() {
+    let (small, large) = (analyse(&closure_loop(1_000)), analyse(&closure_loop(2_000_000)));
+
+    let small = peak_memory(|| Interpreter::interpret(small, &Options::default()).unwrap());
+    let large = peak_memory(|| Interpreter::interpret(large, &Options::default()).unwrap());
+
+    assert!(
+        large <= small + 4096,
+        "{small} bytes for a thousand iterations, but {large} bytes for two million"
+    );
+}
+
+#[test]
+fn mir_closures_are_freed_once_unreachable() {
+    let (small, large) = (lower(&closure_loop(1_000)), lower(&closure_loop(2_000_000)));
+
+    let small = peak_memory(|| Interpreter::interpret_mir(small, &Options::default()).unwrap());
+    let large = peak_memory(|| Interpreter::interpret_mir(large, &Options::default()).unwrap());
+
+    assert!(
+        large <= small + 4096,
+        "{small} bytes for a thousand iterations, but {large} bytes for two million"
+    );
+}
+
+#[test]
+fn long_chains_of_closures_are_freed_without_recursing() {
+    Interpreter::interpret(analyse(&closure_chain(1_000_000)), &Options::default()).unwrap();
+    Interpreter::interpret_mir(lower(&closure_chain(1_000_000)), &Options::default()).unwrap();
+}
@@ -1,16 +1,55 @@
 use catastrophic_core::{profiling::TimeScope, stage::Stage};
 use catastrophic_hir::hir;
+use catastrophic_mir::mir;
 
-use crate::interpreter::{Interpreter, RuntimeError};
+use crate::interpreter::{Interpreter, Options, RuntimeError};
 
-pub struct InterpreterStage;
+pub struct InterpreterStage {
+    options: Options,
+}
+
+/// Runs the program from MIR rather than HIR, so it can be optimized first
+pub struct MirInterpreterStage {
+    options: Options,
+}
+
+impl InterpreterStage {
+    #[must_use]
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+}
 
 impl Stage<Vec<hir::Block>> for InterpreterStage {
     type Output = ();
     type Error = RuntimeError;
 
     fn run(self, input: Vec<hir::Block>, _: &mut TimeScope) -> Result<Self::Output, Self::Error> {
-        Interpreter::interpret(input)
+        Interpreter::interpret(input, &self.options)
+    }
+
+    fn name() -> &'static str {
+        "Runtime"
+    }
+
+    fn error_context() -> &'static str {
+        "Runtime error"
+    }
+}
+
+impl MirInterpreterStage {
+    #[must_use]
+    pub fn new(options: Options) -> Self {
+        Self { options }
+    }
+}
+
+impl Stage<Vec<mir::Block>> for MirInterpreterStage {
+    type Output = ();
+    type Error = RuntimeError;
+
+    fn run(self, input: Vec<mir::Block>, _: &mut TimeScope) -> Result<Self::Output, Self::Error> {
+        Interpreter::interpret_mir(input, &self.options)
     }
 
     fn name() -> &'static str {
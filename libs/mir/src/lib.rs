@@ -1,3 +1,5 @@
 pub mod mir;
 
+mod parse;
 mod pretty;
+mod verify;
@@ -1,3 +1,8 @@
+pub use crate::{
+    parse::{parse, ParseError},
+    pretty::print,
+    verify::{verify, VerifyError, VerifyErrorKind},
+};
 use catastrophic_core::{defines::ValueType, span::Span};
 pub use catastrophic_hir::hir::*;
 
@@ -18,14 +23,14 @@ pub enum TriOp {
     IfThenElse,
 }
 
-#[derive(Debug, Copy, Clone)]
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Function {
     Block(usize),
     BinOp(BinOp),
     TriOp(TriOp),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Value {
     Arg(usize),
     Number(ValueType),
@@ -34,12 +39,15 @@ pub enum Value {
     ImmediateTriOp(TriOp, Box<Value>, Box<Value>, Box<Value>),
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum Instr {
     Command(Command),
     Push(Value),
     ImmediateCall(Function),
     ImmediateConditionalCall(Value, Function, Function),
+    /// A call which ends its block, so can take the place of the block rather than returning to it
+    TailCall(Function),
+    ConditionalTailCall(Value, Function, Function),
 }
 
 #[derive(Debug, Clone)]
@@ -48,4 +56,7 @@ pub struct Block {
     pub args: usize,
     pub instrs: Vec<Span<Instr>>,
     pub name: String,
+    pub source: BlockSource,
+    /// Whether the block may be called through a value, rather than only ever being called directly
+    pub first_class: bool,
 }
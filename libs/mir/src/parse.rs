@@ -0,0 +1,341 @@
+use std::fmt::Display;
+
+use catastrophic_core::{
+    defines::ValueType,
+    span::{Location, Span},
+};
+
+use crate::mir::{BinOp, Block, BlockSource, Command, Function, Instr, TriOp, Value};
+
+/// A line of textual MIR which couldn't be read
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseError {
+    /// The line the problem is on, counting from 1
+    pub line: usize,
+    pub message: String,
+}
+
+/// The unread remainder of a single line
+struct Cursor<'a> {
+    rest: &'a str,
+}
+
+impl<'a> Cursor<'a> {
+    fn new(line: &'a str) -> Self {
+        Self { rest: line }
+    }
+
+    fn eat(&mut self, expected: &str) -> bool {
+        match self.rest.strip_prefix(expected) {
+            Some(rest) => {
+                self.rest = rest;
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn expect(&mut self, expected: &str) -> Result<(), String> {
+        if self.eat(expected) {
+            Ok(())
+        } else {
+            Err(format!("expected `{expected}` but found `{}`", self.rest))
+        }
+    }
+
+    fn take_while(&mut self, predicate: impl Fn(char) -> bool) -> &'a str {
+        let end = self
+            .rest
+            .find(|c| !predicate(c))
+            .unwrap_or(self.rest.len());
+
+        let (taken, rest) = self.rest.split_at(end);
+        self.rest = rest;
+        taken
+    }
+
+    fn number<T: std::str::FromStr>(&mut self) -> Result<T, String> {
+        let negative = self.eat("-");
+        let digits = self.take_while(|c| c.is_ascii_digit());
+        let number = if negative { format!("-{digits}") } else { digits.to_owned() };
+
+        number
+            .parse()
+            .map_err(|_| format!("expected a number but found `{number}{}`", self.rest))
+    }
+
+    fn index(&mut self) -> Result<usize, String> {
+        self.expect("(")?;
+        let index = self.number()?;
+        self.expect(")")?;
+        Ok(index)
+    }
+
+    fn end(&self) -> Result<(), String> {
+        if self.rest.is_empty() {
+            Ok(())
+        } else {
+            Err(format!("unexpected `{}`", self.rest))
+        }
+    }
+}
+
+fn bin_op(name: &str) -> Option<BinOp> {
+    Some(match name {
+        "Plus" => BinOp::Plus,
+        "Minus" => BinOp::Minus,
+        "Multiply" => BinOp::Multiply,
+        "Divide" => BinOp::Divide,
+        "Equals" => BinOp::Equals,
+        "GreaterThan" => BinOp::GreaterThan,
+        "LessThan" => BinOp::LessThan,
+        "Random" => BinOp::Random,
+        _ => return None,
+    })
+}
+
+fn tri_op(name: &str) -> Option<TriOp> {
+    match name {
+        "IfThenElse" => Some(TriOp::IfThenElse),
+        _ => None,
+    }
+}
+
+fn parse_value(cursor: &mut Cursor) -> Result<Value, String> {
+    let name = cursor.take_while(char::is_alphabetic);
+
+    if name.is_empty() {
+        return cursor
+            .number::<ValueType>()
+            .map(Value::Number);
+    }
+
+    if name == "Arg" {
+        return cursor.index().map(Value::Arg);
+    }
+
+    if name == "Block" {
+        return cursor
+            .index()
+            .map(|index| Value::Function(Function::Block(index)));
+    }
+
+    // An operator followed by its operands is applied immediately, otherwise it's pushed as a function
+    if let Some(op) = bin_op(name) {
+        if !cursor.eat("(") {
+            return Ok(Value::Function(Function::BinOp(op)));
+        }
+
+        let x = parse_value(cursor)?;
+        cursor.expect(", ")?;
+        let y = parse_value(cursor)?;
+        cursor.expect(")")?;
+
+        return Ok(Value::ImmediateBinOp(op, Box::new(x), Box::new(y)));
+    }
+
+    if let Some(op) = tri_op(name) {
+        if !cursor.eat("(") {
+            return Ok(Value::Function(Function::TriOp(op)));
+        }
+
+        let x = parse_value(cursor)?;
+        cursor.expect(", ")?;
+        let y = parse_value(cursor)?;
+        cursor.expect(", ")?;
+        let z = parse_value(cursor)?;
+        cursor.expect(")")?;
+
+        return Ok(Value::ImmediateTriOp(op, Box::new(x), Box::new(y), Box::new(z)));
+    }
+
+    Err(format!("unknown value `{name}`"))
+}
+
+// Functions are parsed apart from values, as a branch such as `Plus()` would otherwise look like an operation
+fn parse_function(cursor: &mut Cursor) -> Result<Function, String> {
+    let name = cursor.take_while(char::is_alphabetic);
+
+    if name == "Block" {
+        return cursor.index().map(Function::Block);
+    }
+
+    bin_op(name)
+        .map(Function::BinOp)
+        .or_else(|| tri_op(name).map(Function::TriOp))
+        .ok_or_else(|| format!("expected a function but found `{name}{}`", cursor.rest))
+}
+
+/// The condition and branches of a conditional call, written as `(condition ? x() : y())`
+fn parse_conditional(cursor: &mut Cursor) -> Result<(Value, Function, Function), String> {
+    let value = parse_value(cursor)?;
+    cursor.expect(" ? ")?;
+    let x = parse_function(cursor)?;
+    cursor.expect("() : ")?;
+    let y = parse_function(cursor)?;
+    cursor.expect("())")?;
+    Ok((value, x, y))
+}
+
+fn parse_instr(line: &str) -> Result<Instr, String> {
+    let mut cursor = Cursor::new(line);
+
+    let instr = if cursor.eat("Push[") {
+        let value = parse_value(&mut cursor)?;
+        cursor.expect("]")?;
+        Instr::Push(value)
+    } else if cursor.eat("Call[") {
+        let function = parse_function(&mut cursor)?;
+        cursor.expect("]")?;
+        Instr::ImmediateCall(function)
+    } else if cursor.eat("TailCall[") {
+        let function = parse_function(&mut cursor)?;
+        cursor.expect("]")?;
+        Instr::TailCall(function)
+    } else if cursor.eat("Tail(") {
+        let (value, x, y) = parse_conditional(&mut cursor)?;
+        Instr::ConditionalTailCall(value, x, y)
+    } else if cursor.eat("(") {
+        let (value, x, y) = parse_conditional(&mut cursor)?;
+        Instr::ImmediateConditionalCall(value, x, y)
+    } else {
+        Instr::Command(match cursor.take_while(char::is_alphabetic) {
+            "Call" => Command::Call,
+            "OutputChar" => Command::OutputChar,
+            "OutputNumber" => Command::OutputNumber,
+            "InputChar" => Command::InputChar,
+            "InputNumber" => Command::InputNumber,
+            name => return Err(format!("unknown instruction `{name}{}`", cursor.rest)),
+        })
+    };
+
+    cursor.end()?;
+    Ok(instr)
+}
+
+fn parse_span(text: &str) -> Result<Span<()>, String> {
+    let location = |text: &str| -> Result<Location, String> {
+        let mut cursor = Cursor::new(text);
+        let line = cursor.number()?;
+        cursor.expect(":")?;
+        let col = cursor.number()?;
+        cursor.end()?;
+        Ok(Location::new(line, col))
+    };
+
+    let (start, end) = text
+        .split_once('-')
+        .ok_or_else(|| format!("expected a span but found `{text}`"))?;
+
+    Ok(Span::new(location(start)?, location(end)?, ()))
+}
+
+fn parse_source(details: &str) -> Result<BlockSource, String> {
+    let mut source = BlockSource::default();
+
+    for detail in details.split(", ") {
+        match detail.split_once(": ") {
+            Some(("parent", parent)) => {
+                let mut cursor = Cursor::new(parent);
+                cursor.expect("Block")?;
+                source.parent = Some(cursor.index()?);
+            }
+            Some(("label", label)) => source.label = Some(label.to_owned()),
+            Some(("span", span)) => source.span = Some(parse_span(span)?),
+            _ => return Err(format!("unknown block detail `{detail}`")),
+        }
+    }
+
+    Ok(source)
+}
+
+/// A block header, written as `name(offset: 0, args: 0) [source] {`
+fn parse_header(line: &str) -> Result<Block, String> {
+    let header = line
+        .strip_suffix('{')
+        .ok_or_else(|| format!("expected a block but found `{line}`"))?
+        .trim_end();
+
+    let (name, rest) = header
+        .split_once('(')
+        .ok_or_else(|| format!("expected a block but found `{line}`"))?;
+
+    let mut cursor = Cursor::new(rest);
+    cursor.expect("offset: ")?;
+    let offset = cursor.number()?;
+    cursor.expect(", args: ")?;
+    let args = cursor.number()?;
+    let first_class = !cursor.eat(", direct");
+    cursor.expect(")")?;
+
+    let source = if cursor.eat(" [") {
+        let details = cursor
+            .rest
+            .strip_suffix(']')
+            .ok_or_else(|| format!("expected `]` at the end of `{}`", cursor.rest))?;
+
+        parse_source(details)?
+    } else {
+        cursor.end()?;
+        BlockSource::default()
+    };
+
+    Ok(Block {
+        offset,
+        args,
+        instrs: Vec::new(),
+        name: name.to_owned(),
+        source,
+        first_class,
+    })
+}
+
+/// Read a program back in from the textual MIR format written by [`crate::mir::print`]
+///
+/// Instructions are given empty spans, as the format doesn't record them. The brackets around the
+/// blocks when printed as a whole program are optional, and blank lines are skipped.
+///
+/// # Errors
+///
+/// Returns an error for the first line which couldn't be read
+pub fn parse(input: &str) -> Result<Vec<Block>, ParseError> {
+    let mut blocks = Vec::new();
+    let mut current: Option<Block> = None;
+
+    for (index, line) in input.lines().enumerate() {
+        let error = |message| ParseError { line: index + 1, message };
+        let line = line.trim();
+
+        if line.is_empty() {
+            continue;
+        }
+
+        match &mut current {
+            Some(_) if line == "}" => blocks.extend(current.take()),
+            Some(block) => {
+                let instr = parse_instr(line).map_err(error)?;
+                block
+                    .instrs
+                    .push(Span::new(Location::default(), Location::default(), instr));
+            }
+            None if line == "[" || line == "]" => (),
+            None => current = Some(parse_header(line).map_err(error)?),
+        }
+    }
+
+    match current {
+        Some(block) => Err(ParseError {
+            line: input.lines().count(),
+            message: format!("block `{}` is never closed", block.name),
+        }),
+        None => Ok(blocks),
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+impl std::error::Error for ParseError {}
@@ -1,13 +1,21 @@
 use std::fmt::Write;
 
-use catastrophic_core::pretty::{PrettyDebug, PrettyFormatter};
+use catastrophic_core::pretty::{PrettyDebug, PrettyDebugger, PrettyFormatter};
 
 use crate::mir::{Block, Command, Function, Instr, Value};
 
 impl PrettyDebug for Block {
     fn pretty_debug(&self, fmt: &mut PrettyFormatter) -> std::fmt::Result {
         fmt.write_indent()?;
-        writeln!(fmt, "{{")?;
+        write!(fmt, "{}(offset: {}, args: {}", self.name, self.offset, self.args)?;
+
+        if !self.first_class {
+            write!(fmt, ", direct")?;
+        }
+
+        write!(fmt, ")")?;
+        self.source.pretty_debug(fmt)?;
+        writeln!(fmt, " {{")?;
         fmt.indent();
 
         for instr in &self.instrs {
@@ -40,6 +48,20 @@ impl PrettyDebug for Block {
                     write_function(fmt, y)?;
                     writeln!(fmt, "())")?;
                 }
+                Instr::TailCall(function) => {
+                    write!(fmt, "TailCall[")?;
+                    write_function(fmt, function)?;
+                    writeln!(fmt, "]")?;
+                }
+                Instr::ConditionalTailCall(value, x, y) => {
+                    write!(fmt, "Tail(")?;
+                    write_value(fmt, value)?;
+                    write!(fmt, " ? ")?;
+                    write_function(fmt, x)?;
+                    write!(fmt, "() : ")?;
+                    write_function(fmt, y)?;
+                    writeln!(fmt, "())")?;
+                }
             }
         }
 
@@ -49,6 +71,17 @@ impl PrettyDebug for Block {
     }
 }
 
+/// Write out a program in the textual MIR format, which can be read back in with [`crate::mir::parse`]
+///
+/// Everything but the spans of instructions is kept
+#[must_use]
+pub fn print(blocks: &[Block]) -> String {
+    blocks
+        .iter()
+        .map(|block| PrettyDebugger(block).to_string())
+        .collect()
+}
+
 fn write_function(fmt: &mut PrettyFormatter, function: &Function) -> std::fmt::Result {
     match function {
         Function::Block(index) => write!(fmt, "Block({index})"),
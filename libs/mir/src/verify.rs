@@ -0,0 +1,189 @@
+use std::fmt::Display;
+
+use crate::mir::{Block, Function, Instr, Value};
+
+/// An instruction, or a block as a whole, which breaks one of the rules every MIR program follows
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyError {
+    pub block: usize,
+    /// The offending instruction, if the problem isn't with the block itself
+    pub instr: Option<usize>,
+    pub kind: VerifyErrorKind,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyErrorKind {
+    /// The program has no blocks at all
+    MissingEntry,
+    /// The entry block expects arguments or captures, which nothing will provide
+    EntryTakesArgs,
+    /// A block index which is past the end of the program
+    MissingBlock(usize),
+    /// A parent index which is past the end of the program, or which refers to the block itself
+    InvalidParent(usize),
+    /// An argument index past the `offset + args` arguments the block has
+    ArgOutOfRange { arg: usize, available: usize },
+    /// A block which captures more arguments than the block pushing it has
+    CaptureOutOfRange { block: usize, offset: usize, available: usize },
+    /// A conditional call deciding on a function rather than a number
+    FunctionCondition,
+    /// A tail call which isn't the last instruction of its block
+    MisplacedTailCall,
+}
+
+struct Verifier<'a> {
+    blocks: &'a [Block],
+    block: usize,
+    instr: usize,
+}
+
+impl Verifier<'_> {
+    fn error(&self, kind: VerifyErrorKind) -> VerifyError {
+        VerifyError {
+            block: self.block,
+            instr: Some(self.instr),
+            kind,
+        }
+    }
+
+    fn available(&self) -> usize {
+        let block = &self.blocks[self.block];
+        block.offset + block.args
+    }
+
+    fn function(&self, function: Function) -> Result<(), VerifyError> {
+        let Function::Block(index) = function else {
+            return Ok(());
+        };
+
+        let Some(block) = self.blocks.get(index) else {
+            return Err(self.error(VerifyErrorKind::MissingBlock(index)));
+        };
+
+        // Calling or pushing a block hands it the leading arguments of the current one
+        if block.offset > self.available() {
+            return Err(self.error(VerifyErrorKind::CaptureOutOfRange {
+                block: index,
+                offset: block.offset,
+                available: self.available(),
+            }));
+        }
+
+        Ok(())
+    }
+
+    fn value(&self, value: &Value) -> Result<(), VerifyError> {
+        match value {
+            Value::Arg(arg) if *arg >= self.available() => Err(self.error(VerifyErrorKind::ArgOutOfRange {
+                arg: *arg,
+                available: self.available(),
+            })),
+            Value::Arg(_) | Value::Number(_) => Ok(()),
+            Value::Function(function) => self.function(*function),
+            Value::ImmediateBinOp(_, x, y) => {
+                self.value(x)?;
+                self.value(y)
+            }
+            Value::ImmediateTriOp(_, x, y, z) => {
+                self.value(x)?;
+                self.value(y)?;
+                self.value(z)
+            }
+        }
+    }
+
+    fn condition(&self, value: &Value) -> Result<(), VerifyError> {
+        if let Value::Function(_) = value {
+            return Err(self.error(VerifyErrorKind::FunctionCondition));
+        }
+
+        self.value(value)
+    }
+
+    fn instr(&self, instr: &Instr) -> Result<(), VerifyError> {
+        let last = self.instr + 1 == self.blocks[self.block].instrs.len();
+
+        match instr {
+            Instr::Command(_) => Ok(()),
+            Instr::Push(value) => self.value(value),
+            Instr::TailCall(_) | Instr::ConditionalTailCall(..) if !last => Err(self.error(VerifyErrorKind::MisplacedTailCall)),
+            Instr::ImmediateCall(function) | Instr::TailCall(function) => self.function(*function),
+            Instr::ImmediateConditionalCall(value, x, y) | Instr::ConditionalTailCall(value, x, y) => {
+                self.condition(value)?;
+                self.function(*x)?;
+                self.function(*y)
+            }
+        }
+    }
+}
+
+/// Check that a program is well formed, so can be run or compiled without any surprises
+///
+/// # Errors
+///
+/// Returns the first problem found, in order of block then instruction
+pub fn verify(blocks: &[Block]) -> Result<(), VerifyError> {
+    let Some(entry) = blocks.first() else {
+        return Err(VerifyError {
+            block: 0,
+            instr: None,
+            kind: VerifyErrorKind::MissingEntry,
+        });
+    };
+
+    if entry.offset != 0 || entry.args != 0 {
+        return Err(VerifyError {
+            block: 0,
+            instr: None,
+            kind: VerifyErrorKind::EntryTakesArgs,
+        });
+    }
+
+    for (index, block) in blocks.iter().enumerate() {
+        if let Some(parent) = block
+            .source
+            .parent
+            .filter(|parent| *parent >= blocks.len() || *parent == index)
+        {
+            return Err(VerifyError {
+                block: index,
+                instr: None,
+                kind: VerifyErrorKind::InvalidParent(parent),
+            });
+        }
+
+        for (instr, data) in block.instrs.iter().enumerate() {
+            Verifier { blocks, block: index, instr }.instr(&data.data)?;
+        }
+    }
+
+    Ok(())
+}
+
+impl Display for VerifyErrorKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            VerifyErrorKind::MissingEntry => write!(f, "there is no entry block"),
+            VerifyErrorKind::EntryTakesArgs => write!(f, "the entry block takes arguments"),
+            VerifyErrorKind::MissingBlock(index) => write!(f, "block {index} does not exist"),
+            VerifyErrorKind::InvalidParent(index) => write!(f, "parent block {index} is not valid"),
+            VerifyErrorKind::ArgOutOfRange { arg, available } => write!(f, "argument {arg} is used, but only {available} are available"),
+            VerifyErrorKind::CaptureOutOfRange { block, offset, available } => {
+                write!(f, "block {block} captures {offset} arguments, but only {available} are available")
+            }
+            VerifyErrorKind::FunctionCondition => write!(f, "the condition of a conditional call is a function"),
+            VerifyErrorKind::MisplacedTailCall => write!(f, "a tail call is not the last instruction"),
+        }
+    }
+}
+
+impl Display for VerifyError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.instr {
+            Some(instr) => write!(f, "{} in block {} at instruction {instr}", self.kind, self.block),
+            None => write!(f, "{} in block {}", self.kind, self.block),
+        }
+    }
+}
+
+impl std::error::Error for VerifyError {}
@@ -17,6 +17,18 @@ pub mod error;
 
 pub type Error = RuinousError<State>;
 
+/// Check whether the input lexes as exactly one identifier
+#[must_use]
+pub fn is_identifier(input: &str) -> bool {
+    match Lexer::with_str(input)
+        .collect()
+        .as_deref()
+    {
+        Ok([token]) => token.data == Token::Ident(input.to_owned()),
+        _ => false,
+    }
+}
+
 pub struct Lexer<R> {
     lexer: RuinousLexer<R>,
 }
@@ -22,6 +22,7 @@ enum Mode {
 pub struct State {
     buffer: String,
     number: ValueType,
+    overflowed: bool,
     mode: Mode,
     start: Location,
 }
@@ -55,6 +56,7 @@ impl State {
         Self {
             buffer: String::new(),
             number: 0,
+            overflowed: false,
             mode: Mode::Main,
             start: Location::default(),
         }
@@ -115,8 +117,8 @@ impl State {
                 self.start = input.start;
                 self.mode = Mode::Number;
 
-                self.number = 0;
-                self.number += c as ValueType - '0' as ValueType;
+                self.number = c as ValueType - '0' as ValueType;
+                self.overflowed = false;
                 None
             }
 
@@ -177,12 +179,27 @@ impl State {
 
     fn process_number(&mut self, input: Span<char>) -> StateResult {
         if let c @ '0'..='9' = input.data {
-            self.number *= 10;
-            self.number += c as ValueType - '0' as ValueType;
+            // Once the literal has overflowed the rest of its digits are still consumed, so it's reported as a whole
+            match self
+                .number
+                .checked_mul(10)
+                .and_then(|number| number.checked_add(c as ValueType - '0' as ValueType))
+            {
+                Some(number) => self.number = number,
+                None => self.overflowed = true,
+            }
+
             (None, Continuation::Consume)
         } else {
             self.mode = Mode::Main;
-            (Some(Span::new(self.start, input.start, Token::Integer(self.number))), Continuation::Peek)
+
+            let token = if self.overflowed {
+                Token::IntegerTooLarge
+            } else {
+                Token::Integer(self.number)
+            };
+
+            (Some(Span::new(self.start, input.start, token)), Continuation::Peek)
         }
     }
 
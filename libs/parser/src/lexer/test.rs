@@ -2,7 +2,7 @@
 
 use super::*;
 
-use catastrophic_core::span::Location;
+use catastrophic_core::{defines::ValueType, span::Location};
 
 fn span<D>(data: D, from_line: usize, from_col: usize, to_line: usize, to_col: usize) -> Span<D> {
     Span::new(Location::new(from_line, from_col), Location::new(to_line, to_col), data)
@@ -118,6 +118,8 @@ test_cases! {
     simple_ident("hello", &[span(Token::Ident("hello".to_owned()), 0, 0, 0, 5)])
     simple_string("\"hello\"", &[span(Token::String("hello".to_owned()), 0, 0, 0, 7)])
     simple_integer("10293", &[span(Token::Integer(10293), 0, 0, 0, 5)])
+    largest_integer("9223372036854775807", &[span(Token::Integer(ValueType::MAX), 0, 0, 0, 19)])
+    too_large_integer("9223372036854775808", &[span(Token::IntegerTooLarge, 0, 0, 0, 19)])
     numeric_ident("a1b2c3", &[span(Token::Ident("a1b2c3".to_owned()), 0, 0, 0, 6)])
     emoji_ident("🐉", &[span(Token::Ident("🐉".to_owned()), 0, 0, 0, 1)])
     emoji_string("\"🐉\"", &[span(Token::String("🐉".to_owned()), 0, 0, 0, 3)])
@@ -143,3 +145,15 @@ test_cases! {
     r_paren(")", &[span(Token::Unexpected(')'), 0, 0, 0, 1)])
     comment("# comment\n", &[span(Token::Comment(" comment\n".to_owned()), 0, 0, 0, 10)])
 }
+
+#[test]
+fn identifier_check() {
+    assert!(is_identifier("hello"));
+    assert!(is_identifier("a1b2c3"));
+    assert!(is_identifier("🐉"));
+
+    assert!(!is_identifier(""));
+    assert!(!is_identifier("1x"));
+    assert!(!is_identifier("a b"));
+    assert!(!is_identifier("#a"));
+}
@@ -6,6 +6,7 @@ use catastrophic_core::{
 #[derive(Debug)]
 pub enum ParseError {
     UnexpectedChar(Span<char>),
+    IntegerTooLarge(Span<()>),
     BlockClosedWithoutOpening(Span<()>),
     BlockWithoutClosing(Span<()>),
     LabelWithoutName(Span<()>),
@@ -19,6 +20,7 @@ impl ErrorProvider for ParseError {
     fn write_errors(&self, writer: &mut dyn ErrorWriter) -> std::fmt::Result {
         match self {
             ParseError::UnexpectedChar(span) => writer.error(Some(span.swap(())), &format!("Encountered unexpected `{}`", span.data))?,
+            ParseError::IntegerTooLarge(span) => writer.error(Some(*span), "Encountered an integer too large to be represented")?,
             ParseError::BlockClosedWithoutOpening(span) => writer.error(Some(*span), "Encountered `}` with no corresponding `{`")?,
             ParseError::BlockWithoutClosing(span) => writer.error(Some(*span), "Encountered `{` without corresponding `}`")?,
             ParseError::LabelWithoutName(span) => writer.error(Some(*span), "Encountered `:` without an accompanying symbol name")?,
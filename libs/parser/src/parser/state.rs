@@ -1,10 +1,9 @@
-use std::collections::hash_map::Entry;
-
 use catastrophic_ast::{
     ast::{self, Command},
     token::Token,
 };
 use catastrophic_core::{defines::ValueType, span::Span};
+use indexmap::map::Entry;
 use ruinous::parser::{state::State as ParserState, ParseErrors};
 
 use super::{
@@ -228,7 +227,11 @@ impl State {
     }
 
     fn terminate_block(&mut self) -> (ast::Block, BlockTermination) {
-        let mut block = self.blocks.pop().unwrap();
+        // Closing a block without opening one puts the top level block back, so there is always one to pop
+        let mut block = self
+            .blocks
+            .pop()
+            .expect("top level block was removed");
 
         while let Some(stack_item) = self.stack.pop() {
             let item_span = stack_item.swap(());
@@ -261,6 +264,9 @@ impl State {
             Token::Ident(ident) => self.process_ident(ident, span),
             Token::String(string) => self.process_string(&string, span),
             Token::Integer(value) => self.process_number(value, span),
+            Token::IntegerTooLarge => self
+                .errors
+                .push(ParseError::IntegerTooLarge(span)),
             Token::Arrow => self.process_arrow(span),
             Token::Parens => self.process_command(Command::Call, span),
             Token::Plus => self.process_builtin(Builtin::Plus, span),
@@ -1,12 +1,13 @@
 #![cfg(test)]
 
-use std::collections::hash_map::Entry;
-
 use catastrophic_ast::ast::{Block, Builtin, Command, InstrValue, Instruction, Symbol, SymbolValue};
 use catastrophic_core::{
     defines::ValueType,
+    pretty::PrettyDebugger,
     span::{Location, Span},
 };
+use catastrophic_test_support::generator::{Config, Layout, Program};
+use indexmap::map::Entry;
 
 use crate::lexer::error::LexError;
 
@@ -315,6 +316,25 @@ fn parse_unexpected_char_fails() {
     panic!()
 }
 
+#[test]
+fn parse_too_large_integer_fails() {
+    let parser = Parser::with_str("99999999999999999999");
+    let result = parser.parse();
+
+    if let Err(err) = result {
+        match err {
+            #[allow(clippy::match_on_vec_items)]
+            RuinousError::ParseErrors(errs) if errs.errors.len() == 1 => match errs.errors[0] {
+                ParseError::IntegerTooLarge(s) if s == span((), 0, 0, 0, 20) => return,
+                _ => (),
+            },
+            _ => (),
+        }
+    }
+
+    panic!()
+}
+
 #[test]
 fn parse_unclosed_block_fails() {
     let parser = Parser::with_str("{");
@@ -353,6 +373,23 @@ fn parse_unopened_block_fails() {
     panic!()
 }
 
+#[test]
+fn parse_repeatedly_unopened_blocks_fails() {
+    let parser = Parser::with_str("} { } }");
+    let result = parser.parse();
+
+    if let Err(RuinousError::ParseErrors(errs)) = result {
+        assert_eq!(errs.errors.len(), 2);
+        assert!(errs
+            .errors
+            .iter()
+            .all(|err| matches!(err, ParseError::BlockClosedWithoutOpening(_))));
+        return;
+    }
+
+    panic!()
+}
+
 #[test]
 fn parse_solo_arrow_fails() {
     let parser = Parser::with_str("->");
@@ -485,3 +522,24 @@ fn parse_duplicate_label_fails() {
 
     panic!()
 }
+
+#[test]
+fn parse_generated_programs_the_same_in_any_layout() {
+    let config = Config::default().with_random(true);
+
+    for seed in 0..200 {
+        let program = Program::generate(seed, &config);
+
+        // Spans differ between layouts, so the trees are compared without them
+        let parse = |layout| {
+            let result = Parser::with_str(&program.render(layout)).parse();
+            let ast = result
+                .unwrap_or_else(|_| panic!("seed {seed} failed to parse:\n{program}"))
+                .ast;
+
+            PrettyDebugger(&ast).to_string()
+        };
+
+        assert_eq!(parse(Layout::Pretty), parse(Layout::Compact), "seed {seed}:\n{program}");
+    }
+}
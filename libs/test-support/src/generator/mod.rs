@@ -0,0 +1,592 @@
+use std::{fmt::Display, rc::Rc};
+
+use rand::{rngs::StdRng, seq::SliceRandom, Rng, SeedableRng};
+
+pub use self::program::Layout;
+use self::{
+    program::{Block, Piece},
+    types::{Signature, Type, LIMIT},
+};
+
+mod program;
+mod test;
+mod types;
+
+/// How large and how varied generated programs are
+pub struct Config {
+    budget: usize,
+    max_depth: usize,
+    max_labels: usize,
+    max_lines: usize,
+    random: bool,
+}
+
+/// A random program which is well scoped and always finishes within the budget it was generated for
+///
+/// Every value is tracked with a type while generating, so functions are only called with the arguments they expect,
+/// numbers stay far from overflowing, nothing is divided by zero, and nothing can call itself.
+pub struct Program {
+    root: Block,
+    cost: usize,
+}
+
+/// A name in scope, along with the type of its value
+#[derive(Debug, Clone)]
+struct Binding {
+    name: String,
+    ty: Type,
+}
+
+/// Code which leaves a single value of its type on the stack, or nothing if it has no type
+struct Expr {
+    pieces: Vec<Piece>,
+    ty: Option<Type>,
+    /// The most steps the code can take to run
+    cost: usize,
+}
+
+struct Generator<'a> {
+    rng: StdRng,
+    config: &'a Config,
+    names: usize,
+}
+
+/// How deeply function types may be nested, as in a function taking a function which returns a function
+const TYPE_DEPTH: usize = 2;
+
+const PARAM_BOUNDS: [u64; 3] = [10, 100, 1000];
+const RESULT_BOUNDS: [u64; 3] = [100, 10_000, LIMIT];
+const BIN_OPS: [&str; 6] = ["+", "-", "*", "=", ">", "<"];
+const STRING_CHARS: &[u8] = b"abcdefghijklmnopqrstuvwxyz ";
+
+/// The largest magnitude the result of a builtin can have, given the largest magnitudes of its operands
+fn bin_op_bound(op: &str, x: u64, y: u64) -> u64 {
+    match op {
+        "+" | "-" => x.saturating_add(y),
+        "*" => x.saturating_mul(y),
+        _ => 1,
+    }
+}
+
+fn bound(expr: &Expr) -> u64 {
+    match expr.ty {
+        Some(Type::Number(bound)) => bound,
+        _ => unreachable!("expected a number"),
+    }
+}
+
+fn returns(signature: &Signature, result: Option<&Type>) -> bool {
+    match (&signature.result, result) {
+        (None, None) => true,
+        (Some(actual), Some(expected)) => actual.fits(expected),
+        _ => false,
+    }
+}
+
+impl Expr {
+    fn piece(piece: Piece, ty: Type) -> Self {
+        Self {
+            pieces: vec![piece],
+            ty: Some(ty),
+            cost: 1,
+        }
+    }
+}
+
+impl<'a> Generator<'a> {
+    fn new(seed: u64, config: &'a Config) -> Self {
+        Self {
+            rng: StdRng::seed_from_u64(seed),
+            config,
+            names: 0,
+        }
+    }
+
+    fn name(&mut self, prefix: &str) -> String {
+        self.names += 1;
+        format!("{prefix}{}", self.names)
+    }
+
+    fn pick<T: Copy>(&mut self, items: &[T]) -> T {
+        *items.choose(&mut self.rng).unwrap()
+    }
+
+    fn params(&mut self, depth: usize) -> Vec<Type> {
+        let count = self.rng.gen_range(0..=2);
+
+        (0..count)
+            .map(|_| {
+                if depth > 0 && self.rng.gen_ratio(1, 4) {
+                    let cost = self.rng.gen_range(1..=20);
+                    Type::Function(Rc::new(self.signature(depth - 1, cost)))
+                } else {
+                    Type::Number(self.pick(&PARAM_BOUNDS))
+                }
+            })
+            .collect()
+    }
+
+    fn result(&mut self, depth: usize) -> Option<Type> {
+        match self.rng.gen_range(0..4) {
+            0 => None,
+            1 if depth > 0 => {
+                let cost = self.rng.gen_range(1..=20);
+                Some(Type::Function(Rc::new(self.signature(depth - 1, cost))))
+            }
+            _ => Some(Type::Number(self.pick(&RESULT_BOUNDS))),
+        }
+    }
+
+    /// A random signature, which must cost at least one step so a block can always be made to fit it
+    fn signature(&mut self, depth: usize, cost: usize) -> Signature {
+        Signature {
+            params: self.params(depth),
+            result: self.result(depth),
+            cost,
+        }
+    }
+
+    fn literal(&mut self, max: u64) -> Expr {
+        let value = self.rng.gen_range(0..=max.min(100));
+        Expr::piece(Piece::number(value), Type::Number(value))
+    }
+
+    fn variable(&mut self, scope: &[Binding], expected: &Type) -> Option<Expr> {
+        let candidates = scope
+            .iter()
+            .filter(|binding| binding.ty.fits(expected))
+            .collect::<Vec<_>>();
+
+        let binding = candidates.choose(&mut self.rng)?;
+        Some(Expr::piece(Piece::token(binding.name.clone()), binding.ty.clone()))
+    }
+
+    fn value(&mut self, scope: &[Binding], expected: &Type, budget: usize, depth: usize) -> Expr {
+        match expected {
+            Type::Number(max) => self.number(scope, *max, budget, depth),
+            Type::Function(signature) => self.function(scope, signature, budget, depth),
+        }
+    }
+
+    /// A number no larger in magnitude than the maximum, which takes at most the budget to work out
+    fn number(&mut self, scope: &[Binding], max: u64, budget: usize, depth: usize) -> Expr {
+        if depth == 0 || budget < 2 {
+            let variable = self
+                .rng
+                .gen_bool(0.5)
+                .then(|| self.variable(scope, &Type::Number(max)))
+                .flatten();
+
+            return variable.unwrap_or_else(|| self.literal(max));
+        }
+
+        for _ in 0..3 {
+            let expr = match self.rng.gen_range(0..7) {
+                0 => self.variable(scope, &Type::Number(max)),
+                1 | 2 => self.arithmetic(scope, max, budget, depth - 1),
+                3 => self.call(scope, Some(&Type::Number(max)), budget, depth - 1),
+                4 => self.if_then_else(scope, &Type::Number(max), budget, depth - 1),
+                5 => self.random(max, budget),
+                _ => Some(self.literal(max)),
+            };
+
+            if let Some(expr) = expr {
+                return expr;
+            }
+        }
+
+        self.literal(max)
+    }
+
+    fn arithmetic(&mut self, scope: &[Binding], max: u64, budget: usize, depth: usize) -> Option<Expr> {
+        // Both operands take at least a step, as do pushing and calling the operator
+        if budget < 4 {
+            return None;
+        }
+
+        let rest = budget - 2;
+
+        // Only ever dividing by a literal is the simplest way to be sure of never dividing by zero
+        if self.rng.gen_ratio(1, 6) {
+            let divisor = self.rng.gen_range(1..=9);
+            let x = self.number(scope, max, rest - 1, depth);
+
+            return Some(Expr {
+                ty: Some(Type::Number(bound(&x))),
+                cost: x.cost + 3,
+                pieces: [vec![Piece::number(divisor)], x.pieces, vec![Piece::token("/"), Piece::token("()")]].concat(),
+            });
+        }
+
+        let op = self.pick(&BIN_OPS);
+        let operand_max = match op {
+            "+" | "-" => max / 2,
+            "*" => max.isqrt(),
+            _ if max > 0 => LIMIT,
+            _ => return None,
+        };
+
+        let y = self.number(scope, operand_max, rest / 2, depth);
+        let x = self.number(scope, operand_max, rest - y.cost, depth);
+
+        Some(Expr {
+            ty: Some(Type::Number(bin_op_bound(op, bound(&x), bound(&y)))),
+            cost: x.cost + y.cost + 2,
+            pieces: [y.pieces, x.pieces, vec![Piece::token(op), Piece::token("()")]].concat(),
+        })
+    }
+
+    fn random(&mut self, max: u64, budget: usize) -> Option<Expr> {
+        if !self.config.random || budget < 4 {
+            return None;
+        }
+
+        // The range has to be the right way round, or there's nothing to choose from
+        let high = self.rng.gen_range(0..=max.min(100));
+        let low = self.rng.gen_range(0..=high);
+
+        Some(Expr {
+            pieces: vec![Piece::number(high), Piece::number(low), Piece::token("!"), Piece::token("()")],
+            ty: Some(Type::Number(high)),
+            cost: 4,
+        })
+    }
+
+    fn if_then_else(&mut self, scope: &[Binding], expected: &Type, budget: usize, depth: usize) -> Option<Expr> {
+        if budget < 5 {
+            return None;
+        }
+
+        let rest = budget - 2;
+        let condition = self.number(scope, LIMIT, rest / 3, depth);
+        let rest = rest - condition.cost;
+        let then = self.value(scope, expected, rest / 2, depth);
+        let otherwise = self.value(scope, expected, rest - then.cost, depth);
+
+        let ty = match (&then.ty, &otherwise.ty) {
+            (Some(Type::Number(x)), Some(Type::Number(y))) => Type::Number(*x.max(y)),
+            _ => expected.clone(),
+        };
+
+        Some(Expr {
+            ty: Some(ty),
+            cost: condition.cost + then.cost + otherwise.cost + 2,
+            pieces: [
+                otherwise.pieces,
+                then.pieces,
+                condition.pieces,
+                vec![Piece::token("?"), Piece::token("()")],
+            ]
+            .concat(),
+        })
+    }
+
+    /// A function with the expected signature, which takes at most the budget to push
+    fn function(&mut self, scope: &[Binding], expected: &Rc<Signature>, budget: usize, depth: usize) -> Expr {
+        if depth > 0 {
+            let ty = Type::Function(expected.clone());
+
+            for _ in 0..3 {
+                let expr = match self.rng.gen_range(0..5) {
+                    0 => self.variable(scope, &ty),
+                    1 => self.builtin(expected),
+                    2 => self.if_then_else(scope, &ty, budget, depth - 1),
+                    3 => self.call(scope, Some(&ty), budget, depth - 1),
+                    _ => None,
+                };
+
+                if let Some(expr) = expr {
+                    return expr;
+                }
+            }
+        }
+
+        let (block, signature) = self.block(scope, expected, depth);
+
+        Expr {
+            pieces: vec![Piece::Block(block)],
+            ty: Some(Type::Function(signature)),
+            cost: 1,
+        }
+    }
+
+    fn builtin(&mut self, expected: &Signature) -> Option<Expr> {
+        let ([Type::Number(x), Type::Number(y)], Some(Type::Number(max))) = (expected.params.as_slice(), &expected.result) else {
+            return None;
+        };
+
+        let ops = BIN_OPS
+            .into_iter()
+            .filter(|op| bin_op_bound(op, *x, *y) <= *max)
+            .collect::<Vec<_>>();
+
+        let op = *ops.choose(&mut self.rng)?;
+        let signature = Signature {
+            params: expected.params.clone(),
+            result: Some(Type::Number(bin_op_bound(op, *x, *y))),
+            cost: 1,
+        };
+
+        (signature.cost <= expected.cost).then(|| Expr::piece(Piece::token(op), Type::Function(Rc::new(signature))))
+    }
+
+    /// A block which can be used wherever a function with the signature is expected, along with its exact signature
+    fn block(&mut self, scope: &[Binding], signature: &Signature, depth: usize) -> (Block, Rc<Signature>) {
+        let mut scope = scope.to_vec();
+        let mut args = Vec::new();
+
+        for ty in &signature.params {
+            let name = self.name("a");
+            args.push(name.clone());
+            scope.push(Binding { name, ty: ty.clone() });
+        }
+
+        let (mut block, cost, result) = self.body(&mut scope, signature.result.as_ref(), signature.cost, depth.saturating_sub(1));
+        block.args = args;
+
+        let signature = Signature {
+            params: signature.params.clone(),
+            result,
+            cost,
+        };
+
+        (block, Rc::new(signature))
+    }
+
+    /// A call to a function in scope or to a new block, leaving a value which fits the result if there is one
+    fn call(&mut self, scope: &[Binding], result: Option<&Type>, budget: usize, depth: usize) -> Option<Expr> {
+        // Pushing the function, calling it, and each of the arguments all take at least a step
+        let affordable = |signature: &Signature| signature.cost + signature.params.len() + 2 <= budget;
+
+        let candidates = scope
+            .iter()
+            .filter_map(|binding| match &binding.ty {
+                Type::Function(signature) if returns(signature, result) && affordable(signature) => Some((binding.name.clone(), signature.clone())),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        let (callee, signature) = if !candidates.is_empty() && self.rng.gen_bool(0.5) {
+            let (name, signature) = candidates
+                .choose(&mut self.rng)
+                .unwrap()
+                .clone();
+
+            (vec![Piece::token(name)], signature)
+        } else {
+            let params = self.params(TYPE_DEPTH);
+            let minimum = usize::from(result.is_some());
+            let available = budget.checked_sub(params.len() + 2)?;
+
+            if available < minimum {
+                return None;
+            }
+
+            let signature = Signature {
+                params,
+                result: result.cloned(),
+                cost: self.rng.gen_range(minimum..=available),
+            };
+
+            let (block, signature) = self.block(scope, &signature, depth);
+            (vec![Piece::Block(block)], signature)
+        };
+
+        let mut remaining = budget - signature.cost - 2;
+        let mut args = Vec::new();
+        let mut cost = signature.cost + 2;
+
+        for (index, param) in signature.params.iter().enumerate() {
+            let later = signature.params.len() - index - 1;
+            let arg = self.value(scope, param, (remaining - later) / (later + 1), depth);
+
+            remaining -= arg.cost;
+            cost += arg.cost;
+            args.extend(arg.pieces);
+        }
+
+        Some(Expr {
+            pieces: [args, callee, vec![Piece::token("()")]].concat(),
+            ty: signature.result.clone(),
+            cost,
+        })
+    }
+
+    /// A line which leaves the stack as it found it
+    fn statement(&mut self, scope: &[Binding], budget: usize, depth: usize) -> Option<Expr> {
+        match self.rng.gen_range(0..4) {
+            0 | 1 if budget >= 2 => {
+                let expr = self.number(scope, LIMIT, budget - 1, depth);
+
+                Some(Expr {
+                    pieces: [expr.pieces, vec![Piece::token(".")]].concat(),
+                    ty: None,
+                    cost: expr.cost + 1,
+                })
+            }
+            2 if budget >= 2 => {
+                let length = self
+                    .rng
+                    .gen_range(1..=8)
+                    .min(budget / 2);
+                let string = (0..length)
+                    .map(|_| char::from(self.pick(STRING_CHARS)))
+                    .collect::<String>();
+
+                Some(Expr {
+                    pieces: [vec![Piece::token(format!("\"{string}\""))], vec![Piece::token(","); length]].concat(),
+                    ty: None,
+                    cost: length * 2,
+                })
+            }
+            _ => self.call(scope, None, budget, depth),
+        }
+    }
+
+    fn label(&mut self, scope: &[Binding], depth: usize) -> (String, Piece, Type) {
+        match self.rng.gen_range(0..4) {
+            0 => {
+                let value = self.rng.gen_range(0..=1000);
+                (self.name("n"), Piece::number(value), Type::Number(value))
+            }
+            1 => {
+                let op = self.pick(&BIN_OPS);
+                let bound = self.pick(&PARAM_BOUNDS);
+
+                let signature = Signature {
+                    params: vec![Type::Number(bound), Type::Number(bound)],
+                    result: Some(Type::Number(bin_op_bound(op, bound, bound))),
+                    cost: 1,
+                };
+
+                (self.name("op"), Piece::token(op), Type::Function(Rc::new(signature)))
+            }
+            _ => {
+                let cost = self
+                    .rng
+                    .gen_range(1..=(self.config.budget / 4).max(1));
+
+                let signature = self.signature(TYPE_DEPTH, cost);
+                let (block, signature) = self.block(scope, &signature, depth);
+
+                (self.name("f"), Piece::Block(block), Type::Function(signature))
+            }
+        }
+    }
+
+    /// The labels and lines of a block, leaving a value which fits the result if there is one
+    ///
+    /// Returns the most steps the block takes to run, and the exact type of its result
+    fn body(&mut self, scope: &mut Vec<Binding>, result: Option<&Type>, budget: usize, depth: usize) -> (Block, usize, Option<Type>) {
+        let outer = scope.len();
+        let mut block = Block::default();
+        let mut lines = 0;
+
+        if depth > 0 {
+            for _ in 0..self
+                .rng
+                .gen_range(0..=self.config.max_labels)
+            {
+                let (name, value, ty) = self.label(scope, depth);
+                block.labels.push((name.clone(), value));
+                scope.push(Binding { name, ty });
+            }
+
+            lines = self
+                .rng
+                .gen_range(1..=self.config.max_lines);
+        }
+
+        // Whatever else happens, there has to be a step left over to push the result
+        let reserved = usize::from(result.is_some());
+        let mut remaining = budget - reserved;
+        let mut cost = 0;
+
+        for _ in 0..lines {
+            if let Some(line) = self.statement(scope, remaining, depth) {
+                remaining -= line.cost;
+                cost += line.cost;
+                block.lines.push(line.pieces);
+            }
+        }
+
+        let result = result.map(|expected| {
+            let expr = self.value(scope, expected, remaining + reserved, depth);
+            cost += expr.cost;
+            block.lines.push(expr.pieces);
+            expr.ty.unwrap()
+        });
+
+        scope.truncate(outer);
+        (block, cost, result)
+    }
+}
+
+impl Config {
+    /// The default most steps a generated program may take to run
+    pub const DEFAULT_BUDGET: usize = 10_000;
+
+    #[must_use]
+    pub fn with_budget(mut self, budget: usize) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    #[must_use]
+    pub fn budget(&self) -> usize {
+        self.budget
+    }
+
+    #[must_use]
+    pub fn with_max_depth(mut self, depth: usize) -> Self {
+        self.max_depth = depth;
+        self
+    }
+
+    /// Whether to use `!`, which makes the output differ from one run to the next
+    #[must_use]
+    pub fn with_random(mut self, random: bool) -> Self {
+        self.random = random;
+        self
+    }
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            budget: Self::DEFAULT_BUDGET,
+            max_depth: 4,
+            max_labels: 2,
+            max_lines: 3,
+            random: false,
+        }
+    }
+}
+
+impl Program {
+    /// Generate a program from a seed, which always gives the same program for the same seed and config
+    #[must_use]
+    pub fn generate(seed: u64, config: &Config) -> Self {
+        let mut generator = Generator::new(seed, config);
+        let (root, cost, _) = generator.body(&mut Vec::new(), None, config.budget, config.max_depth);
+
+        Self { root, cost }
+    }
+
+    /// The most steps the program can take to run, counting every instruction run along the way
+    #[must_use]
+    pub fn cost(&self) -> usize {
+        self.cost
+    }
+
+    #[must_use]
+    pub fn render(&self, layout: Layout) -> String {
+        self.root.render(layout)
+    }
+}
+
+impl Display for Program {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.render(Layout::Pretty))
+    }
+}
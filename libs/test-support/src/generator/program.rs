@@ -0,0 +1,118 @@
+use std::fmt::Write;
+
+/// A block of a generated program, kept as a tree so it can be written out in more than one layout
+#[derive(Debug, Clone, Default)]
+pub struct Block {
+    pub args: Vec<String>,
+    pub labels: Vec<(String, Piece)>,
+    /// Each line leaves the stack as it found it, apart from the last line of a block with a result
+    pub lines: Vec<Vec<Piece>>,
+}
+
+#[derive(Debug, Clone)]
+pub enum Piece {
+    Token(String),
+    Block(Block),
+}
+
+/// How a generated program is written out, which shouldn't change what it means
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Layout {
+    /// Indented, with each label and line on a line of its own
+    Pretty,
+    /// Everything on a single line
+    Compact,
+}
+
+struct Writer {
+    layout: Layout,
+    output: String,
+    indent: usize,
+}
+
+impl Piece {
+    pub fn token(token: impl Into<String>) -> Self {
+        Piece::Token(token.into())
+    }
+
+    pub fn number(value: u64) -> Self {
+        Piece::Token(value.to_string())
+    }
+}
+
+impl Writer {
+    fn newline(&mut self) {
+        match self.layout {
+            Layout::Pretty => {
+                self.output.push('\n');
+                self.output
+                    .push_str(&"    ".repeat(self.indent));
+            }
+            Layout::Compact => self.output.push(' '),
+        }
+    }
+
+    fn piece(&mut self, piece: &Piece) {
+        match piece {
+            Piece::Token(token) => self.output.push_str(token),
+            Piece::Block(block) => self.block(block),
+        }
+    }
+
+    fn line(&mut self, line: &[Piece]) {
+        for (index, piece) in line.iter().enumerate() {
+            if index > 0 {
+                self.output.push(' ');
+            }
+
+            self.piece(piece);
+        }
+    }
+
+    fn block(&mut self, block: &Block) {
+        for arg in &block.args {
+            let _ = write!(self.output, "{arg} -> ");
+        }
+
+        self.output.push('{');
+        self.indent += 1;
+        self.contents(block);
+        self.indent -= 1;
+        self.newline();
+        self.output.push('}');
+    }
+
+    fn contents(&mut self, block: &Block) {
+        for (name, value) in &block.labels {
+            self.newline();
+            let _ = write!(self.output, "{name}: ");
+            self.piece(value);
+        }
+
+        if self.layout == Layout::Pretty && !block.labels.is_empty() && !block.lines.is_empty() {
+            self.output.push('\n');
+        }
+
+        for line in &block.lines {
+            self.newline();
+            self.line(line);
+        }
+    }
+}
+
+impl Block {
+    /// Write out the contents of the block as a whole program
+    pub fn render(&self, layout: Layout) -> String {
+        let mut writer = Writer {
+            layout,
+            output: String::new(),
+            indent: 0,
+        };
+
+        writer.contents(self);
+
+        let mut output = writer.output.trim_start().to_owned();
+        output.push('\n');
+        output
+    }
+}
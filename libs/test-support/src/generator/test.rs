@@ -0,0 +1,66 @@
+#![cfg(test)]
+
+use super::*;
+
+fn programs(config: &Config) -> impl Iterator<Item = Program> + '_ {
+    (0..200).map(|seed| Program::generate(seed, config))
+}
+
+#[test]
+fn same_seed_gives_same_program() {
+    let config = Config::default();
+
+    assert_eq!(Program::generate(7, &config).to_string(), Program::generate(7, &config).to_string());
+    assert_ne!(Program::generate(7, &config).to_string(), Program::generate(8, &config).to_string());
+}
+
+#[test]
+fn programs_stay_within_budget() {
+    for budget in [1, 10, 100, 1000] {
+        let config = Config::default().with_budget(budget);
+
+        for program in programs(&config) {
+            assert!(program.cost() <= budget, "{program}");
+        }
+    }
+}
+
+#[test]
+fn programs_use_every_construct() {
+    let source = programs(&Config::default())
+        .map(|program| program.to_string())
+        .collect::<String>();
+
+    for construct in ["->", ":", "{", "()", "?", ".", ",", "\"", "+", "-", "*", "/", "=", "<", ">"] {
+        assert!(source.contains(construct), "no program uses `{construct}`");
+    }
+
+    assert!(!source.contains('!'));
+}
+
+#[test]
+fn random_is_only_used_when_enabled() {
+    let source = programs(&Config::default().with_random(true))
+        .map(|program| program.to_string())
+        .collect::<String>();
+
+    assert!(source.contains('!'));
+}
+
+#[test]
+fn layouts_only_differ_in_whitespace() {
+    for program in programs(&Config::default()) {
+        let pretty = program.render(Layout::Pretty);
+        let compact = program.render(Layout::Compact);
+
+        assert_eq!(
+            pretty
+                .split_whitespace()
+                .collect::<Vec<_>>(),
+            compact
+                .split_whitespace()
+                .collect::<Vec<_>>()
+        );
+        assert_eq!(compact.lines().count(), 1);
+    }
+}
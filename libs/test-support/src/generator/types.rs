@@ -0,0 +1,46 @@
+use std::rc::Rc;
+
+/// The largest magnitude any number in a generated program may reach, well clear of overflowing
+pub const LIMIT: u64 = 1 << 40;
+
+/// What the generator knows about a value, so it only ever uses it in ways which can't go wrong
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Type {
+    /// A number no larger in magnitude than the bound
+    Number(u64),
+    Function(Rc<Signature>),
+}
+
+/// What a function takes from the stack, what it leaves behind, and the most steps a call to it can take
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Signature {
+    pub params: Vec<Type>,
+    pub result: Option<Type>,
+    pub cost: usize,
+}
+
+impl Type {
+    /// Whether a value of this type can be used wherever the expected type is needed
+    pub fn fits(&self, expected: &Type) -> bool {
+        match (self, expected) {
+            (Type::Number(bound), Type::Number(expected)) => bound <= expected,
+            (Type::Function(signature), Type::Function(expected)) => signature.fits(expected),
+            _ => false,
+        }
+    }
+}
+
+impl Signature {
+    /// Whether a function with this signature can be called wherever the expected one could be
+    ///
+    /// Parameters have to match exactly, while the result may be smaller and the call may be cheaper
+    pub fn fits(&self, expected: &Signature) -> bool {
+        let result = match (&self.result, &expected.result) {
+            (None, None) => true,
+            (Some(result), Some(expected)) => result.fits(expected),
+            _ => false,
+        };
+
+        result && self.params == expected.params && self.cost <= expected.cost
+    }
+}